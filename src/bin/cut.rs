@@ -0,0 +1,29 @@
+use std::env;
+use std::fs;
+
+use main_error::MainError;
+use tf_demo_parser::demo::cut::cut_demo;
+
+fn main() -> Result<(), MainError> {
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt::init();
+
+    #[cfg(feature = "better_panic")]
+    better_panic::install();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 5 {
+        println!("usage: cut_demo <input> <output> <start_tick> <end_tick>");
+        return Ok(());
+    }
+    let path = args[1].clone();
+    let out_path = args[2].clone();
+    let start_tick: u32 = args[3].parse()?;
+    let end_tick: u32 = args[4].parse()?;
+    let file = fs::read(path)?;
+
+    let out = cut_demo(&file, start_tick.into(), end_tick.into())?;
+    fs::write(out_path, out)?;
+
+    Ok(())
+}
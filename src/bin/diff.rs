@@ -0,0 +1,30 @@
+use std::env;
+use std::fs;
+
+use main_error::MainError;
+use tf_demo_parser::demo::diff::diff_demos;
+
+fn main() -> Result<(), MainError> {
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt::init();
+
+    #[cfg(feature = "better_panic")]
+    better_panic::install();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        println!("usage: diff_demo <left> <right>");
+        return Ok(());
+    }
+    let left = fs::read(&args[1])?;
+    let right = fs::read(&args[2])?;
+
+    let diff = diff_demos(&left, &right)?;
+    println!("{}", diff);
+
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
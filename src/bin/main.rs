@@ -3,6 +3,7 @@ use std::fs;
 
 use main_error::MainError;
 pub use tf_demo_parser::{Demo, DemoParser, Parse, ParseError, ParserState, Stream};
+use tf_demo_parser::demo::data::SteamIdFormat;
 use tf_demo_parser::demo::parser::player_summary_analyzer::PlayerSummaryAnalyzer;
 
 
@@ -25,6 +26,10 @@ fn main() -> Result<(), MainError> {
     let path = args[1].clone();
     let all = args.contains(&std::string::String::from("all"));
     let detailed_summaries = args.contains(&std::string::String::from("detailed_summaries"));
+    let steam_id_format = match args.iter().find_map(|arg| arg.strip_prefix("steamid=")) {
+        Some(format) => format.parse()?,
+        None => SteamIdFormat::default(),
+    };
     let file = fs::read(path)?;
     let demo = Demo::new(&file);
 
@@ -36,7 +41,7 @@ fn main() -> Result<(), MainError> {
             DemoParser::new(demo.get_stream())
         };
         let (_, state) = parser.parse()?;
-        println!("{}", serde_json::to_string(&state)?);
+        println!("{}", serde_json::to_string(&steam_id_format.formatted(&state))?);
     } else {
         let parser = DemoParser::new_with_analyser(demo.get_stream(), PlayerSummaryAnalyzer::new());
         let (header, state) = parser.parse()?;
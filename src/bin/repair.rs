@@ -0,0 +1,28 @@
+use std::env;
+use std::fs;
+
+use main_error::MainError;
+use tf_demo_parser::demo::repair::repair_demo;
+
+fn main() -> Result<(), MainError> {
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt::init();
+
+    #[cfg(feature = "better_panic")]
+    better_panic::install();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        println!("usage: repair_demo <input> <output>");
+        return Ok(());
+    }
+    let path = args[1].clone();
+    let out_path = args[2].clone();
+    let file = fs::read(path)?;
+
+    let (out, report) = repair_demo(&file)?;
+    fs::write(out_path, out)?;
+    eprintln!("{}", report);
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::env;
+use std::fs;
+use std::io::BufWriter;
+use std::path::Path;
+
+use main_error::MainError;
+use serde_json::json;
+use tf_demo_parser::demo::parser::voiceanalyser::{Speaker, VoiceAnalyser};
+pub use tf_demo_parser::{Demo, DemoParser, Parse, ParseError, ParserState, Stream};
+
+#[cfg(feature = "jemallocator")]
+#[global_allocator]
+static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
+
+fn main() -> Result<(), MainError> {
+    #[cfg(feature = "better_panic")]
+    better_panic::install();
+
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        println!("usage: {} <demo> <output directory>", args[0]);
+        return Ok(());
+    }
+    let path = args[1].clone();
+    let output = Path::new(&args[2]);
+    let file = fs::read(path)?;
+    let demo = Demo::new(&file);
+    let parser = DemoParser::new_with_analyser(demo.get_stream(), VoiceAnalyser::new());
+    let (_, recording) = parser.parse()?;
+
+    fs::create_dir_all(output)?;
+
+    let mut speakers = Vec::with_capacity(recording.speakers.len());
+    for speaker in recording.speakers.iter() {
+        let file_name = match speaker.user_id {
+            Some(user_id) => format!("user_{}", u16::from(user_id)),
+            None => format!("client_{}", speaker.entity_id),
+        };
+
+        // steam voice can be stored as a playable file, for other codecs we dump the raw codec data
+        let file_name = if recording.is_steam_voice() {
+            let file_name = format!("{}.ogg", file_name);
+            let writer = BufWriter::new(fs::File::create(output.join(&file_name))?);
+            speaker.write_ogg_opus(writer, recording.interval_per_tick)?;
+            file_name
+        } else {
+            let file_name = format!("{}.raw", file_name);
+            fs::write(output.join(&file_name), raw_data(speaker))?;
+            file_name
+        };
+
+        let mut offset = 0;
+        let packets: Vec<_> = speaker
+            .packets
+            .iter()
+            .map(|packet| {
+                let tick = u32::from(packet.tick);
+                let entry = json!({
+                    "tick": tick,
+                    "time": tick as f32 * recording.interval_per_tick,
+                    "offset": offset,
+                    "length": packet.data.len(),
+                    "proximity": packet.proximity,
+                });
+                offset += packet.data.len();
+                entry
+            })
+            .collect();
+
+        speakers.push(json!({
+            "entityId": u32::from(speaker.entity_id),
+            "userId": speaker.user_id,
+            "name": speaker.name,
+            "file": file_name,
+            "packets": packets,
+        }));
+    }
+
+    let summary = json!({
+        "codec": recording.codec,
+        "quality": recording.quality,
+        "sampleRate": recording.sample_rate,
+        "intervalPerTick": recording.interval_per_tick,
+        "speakers": speakers,
+    });
+    fs::write(
+        output.join("voice.json"),
+        serde_json::to_string_pretty(&summary)?,
+    )?;
+
+    println!(
+        "extracted voice for {} speakers to {}",
+        recording.speakers.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn raw_data(speaker: &Speaker) -> Vec<u8> {
+    speaker
+        .packets
+        .iter()
+        .flat_map(|packet| packet.data.iter().copied())
+        .collect()
+}
@@ -0,0 +1,122 @@
+//! Cutting a tick range out of a demo into a standalone demo
+
+use crate::demo::data::DemoTick;
+use crate::demo::header::Header;
+use crate::demo::message::packetentities::UpdateType;
+use crate::demo::message::{Message, NetTickMessage};
+use crate::demo::packet::message::{MessagePacket, MessagePacketMeta};
+use crate::demo::packet::Packet;
+use crate::demo::parser::entitystateanalyser::EntityStateAnalyser;
+use crate::demo::parser::{DemoHandler, RawPacketStream};
+use crate::{Demo, DemoWriter, ParserState, Result};
+use bitbuffer::BitRead;
+
+/// Cut the ticks from `start_tick` till `end_tick` (inclusive) out of a demo
+///
+/// The signon data is copied from the input demo, followed by the full string tables and
+/// entity state at the start tick, so the resulting demo can be played back on its own.
+/// The ticks in the resulting demo start from 0.
+pub fn cut_demo(input: &[u8], start_tick: DemoTick, end_tick: DemoTick) -> Result<Vec<u8>> {
+    let demo = Demo::new(input);
+    let mut stream = demo.get_stream();
+    let header = Header::read(&mut stream)?;
+    let mut packets = RawPacketStream::new(stream);
+
+    let mut handler = DemoHandler::parse_all_with_analyser(EntityStateAnalyser::new());
+    handler.handle_header(&header);
+    let mut writer = DemoWriter::new(header);
+
+    let mut started = false;
+    let mut last_meta = MessagePacketMeta::default();
+    let mut last_net_tick = None;
+
+    while let Some(packet) = packets.next(&handler.state_handler)? {
+        let tick = packet.tick();
+        match &packet {
+            Packet::Signon(_) | Packet::DataTables(_) | Packet::SyncTick(_) => {
+                writer.write_packet(packet.clone())?;
+            }
+            // replaced by the string tables at the start tick
+            Packet::StringTables(_) => {}
+            Packet::Stop(_) => break,
+            Packet::Message(message_packet) if tick < start_tick => {
+                last_meta = message_packet.meta.clone();
+                if let Some(net_tick) = find_net_tick(message_packet) {
+                    last_net_tick = Some(net_tick.clone());
+                }
+            }
+            _ if tick < start_tick => {}
+            _ if tick > end_tick => break,
+            _ => {
+                if !started {
+                    started = true;
+                    write_start_state(&mut writer, &handler, last_net_tick.take(), &last_meta)?;
+                }
+
+                let mut out_packet = packet.clone();
+                out_packet.set_tick(tick - start_tick);
+                if let Packet::Message(message_packet) = &mut out_packet {
+                    expand_entering_entities(message_packet, &handler.state_handler);
+                }
+                writer.write_packet(out_packet)?;
+            }
+        }
+        handler.handle_packet(packet)?;
+    }
+
+    writer.finish()
+}
+
+fn find_net_tick<'a>(packet: &'a MessagePacket) -> Option<&'a NetTickMessage> {
+    packet
+        .messages
+        .iter()
+        .rev()
+        .find_map(|message| match message {
+            Message::NetTick(net_tick) => Some(net_tick),
+            _ => None,
+        })
+}
+
+/// Write the string tables and entities as they are at the start of the cut
+fn write_start_state<'a>(
+    writer: &mut DemoWriter<'a>,
+    handler: &DemoHandler<'a, EntityStateAnalyser>,
+    net_tick: Option<NetTickMessage>,
+    meta: &MessagePacketMeta,
+) -> Result<()> {
+    let state = handler.get_parser_state();
+    writer.write_packet(Packet::StringTables(
+        state.string_table_store.snapshot(DemoTick::default()),
+    ))?;
+
+    let entity_state = handler.borrow_output();
+    if entity_state.entities.is_empty() {
+        return Ok(());
+    }
+
+    // the following delta updates are based on the server tick of the last skipped update
+    let mut messages = Vec::with_capacity(2);
+    messages.extend(net_tick.map(Message::NetTick));
+    messages.push(Message::PacketEntities(entity_state.full_update()));
+    writer.write_packet(Packet::Message(MessagePacket {
+        tick: DemoTick::default(),
+        messages,
+        meta: meta.clone(),
+        padding: None,
+    }))
+}
+
+/// Include the baseline props into entering entities,
+/// the baselines from the original demo don't exist in the cut demo
+pub(crate) fn expand_entering_entities(packet: &mut MessagePacket, state: &ParserState) {
+    for message in packet.messages.iter_mut() {
+        if let Message::PacketEntities(entity_message) = message {
+            for entity in entity_message.entities.iter_mut() {
+                if entity.update_type == UpdateType::Enter {
+                    entity.props = entity.props(state).collect();
+                }
+            }
+        }
+    }
+}
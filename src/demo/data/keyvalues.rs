@@ -0,0 +1,287 @@
+use crate::demo::data::MaybeUtf8String;
+use crate::{ReadResult, Stream};
+use bitbuffer::{BitError, BitRead, BitWrite, BitWriteStream, LittleEndian};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+const TYPE_NONE: u8 = 0;
+const TYPE_STRING: u8 = 1;
+const TYPE_INT: u8 = 2;
+const TYPE_FLOAT: u8 = 3;
+const TYPE_PTR: u8 = 4;
+const TYPE_WSTRING: u8 = 5;
+const TYPE_COLOR: u8 = 6;
+const TYPE_UINT64: u8 = 7;
+/// Marks the end of a list of keys
+const TYPE_END: u8 = 8;
+
+/// Maximum nesting of sub keys, to prevent crafted input from overflowing the stack
+const MAX_DEPTH: usize = 32;
+
+/// A list of keys as stored by the engine's KeyValues, read and written in the binary format
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct KeyValues {
+    pub entries: Vec<KeyValue>,
+}
+
+impl KeyValues {
+    pub fn get(&self, key: &str) -> Option<&KeyValueData> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key.as_ref() == key)
+            .map(|entry| &entry.value)
+    }
+
+    fn write_text(&self, f: &mut Formatter, depth: usize) -> fmt::Result {
+        for entry in &self.entries {
+            let indent = "\t".repeat(depth);
+            write!(f, "{}", indent)?;
+            write_quoted(f, entry.key.as_ref())?;
+            match &entry.value {
+                KeyValueData::SubKeys(keys) => {
+                    writeln!(f, "\n{}{{", indent)?;
+                    keys.write_text(f, depth + 1)?;
+                    writeln!(f, "{}}}", indent)?;
+                }
+                value => {
+                    write!(f, "\t")?;
+                    write_quoted(f, &value.to_string())?;
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyValue {
+    pub key: MaybeUtf8String,
+    pub value: KeyValueData,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyValueData {
+    SubKeys(KeyValues),
+    String(MaybeUtf8String),
+    Int(i32),
+    Float(f32),
+    Pointer(u32),
+    /// Wide strings are not serialized by the engine and carry no data
+    WString,
+    Color([u8; 4]),
+    UInt64(u64),
+}
+
+impl KeyValueData {
+    fn kind(&self) -> u8 {
+        match self {
+            KeyValueData::SubKeys(_) => TYPE_NONE,
+            KeyValueData::String(_) => TYPE_STRING,
+            KeyValueData::Int(_) => TYPE_INT,
+            KeyValueData::Float(_) => TYPE_FLOAT,
+            KeyValueData::Pointer(_) => TYPE_PTR,
+            KeyValueData::WString => TYPE_WSTRING,
+            KeyValueData::Color(_) => TYPE_COLOR,
+            KeyValueData::UInt64(_) => TYPE_UINT64,
+        }
+    }
+}
+
+impl BitRead<'_, LittleEndian> for KeyValues {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        read_key_values(stream, 0)
+    }
+}
+
+fn read_key_values(stream: &mut Stream, depth: usize) -> ReadResult<KeyValues> {
+    let mut entries = Vec::new();
+    loop {
+        let kind: u8 = stream.read()?;
+        if kind == TYPE_END {
+            return Ok(KeyValues { entries });
+        }
+        let key = stream.read()?;
+        let value = match kind {
+            TYPE_NONE if depth < MAX_DEPTH => {
+                KeyValueData::SubKeys(read_key_values(stream, depth + 1)?)
+            }
+            TYPE_STRING => KeyValueData::String(stream.read()?),
+            TYPE_INT => KeyValueData::Int(stream.read()?),
+            TYPE_FLOAT => KeyValueData::Float(stream.read()?),
+            TYPE_PTR => KeyValueData::Pointer(stream.read()?),
+            TYPE_WSTRING => KeyValueData::WString,
+            TYPE_COLOR => KeyValueData::Color(stream.read()?),
+            TYPE_UINT64 => KeyValueData::UInt64(stream.read()?),
+            // either an unknown type or sub keys nested deeper than the limit
+            _ => {
+                return Err(BitError::UnmatchedDiscriminant {
+                    discriminant: kind as usize,
+                    enum_name: "KeyValueData".to_string(),
+                })
+            }
+        };
+        entries.push(KeyValue { key, value });
+    }
+}
+
+impl BitWrite<LittleEndian> for KeyValues {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        for entry in &self.entries {
+            entry.value.kind().write(stream)?;
+            entry.key.write(stream)?;
+            match &entry.value {
+                KeyValueData::SubKeys(keys) => keys.write(stream)?,
+                KeyValueData::String(value) => value.write(stream)?,
+                KeyValueData::Int(value) => value.write(stream)?,
+                KeyValueData::Float(value) => value.write(stream)?,
+                KeyValueData::Pointer(value) => value.write(stream)?,
+                KeyValueData::WString => {}
+                KeyValueData::Color(value) => value.write(stream)?,
+                KeyValueData::UInt64(value) => value.write(stream)?,
+            }
+        }
+        TYPE_END.write(stream)
+    }
+}
+
+/// Formats the key values in the text format used by the engine for `.res` and `.vdf` files
+impl Display for KeyValues {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_text(f, 0)
+    }
+}
+
+/// Formats the value as it would appear in text key values, sub keys are formatted as a text block
+impl Display for KeyValueData {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            KeyValueData::SubKeys(keys) => keys.fmt(f),
+            KeyValueData::String(value) => value.fmt(f),
+            KeyValueData::Int(value) => value.fmt(f),
+            KeyValueData::Float(value) => value.fmt(f),
+            KeyValueData::Pointer(value) => value.fmt(f),
+            KeyValueData::WString => Ok(()),
+            KeyValueData::Color([r, g, b, a]) => write!(f, "{} {} {} {}", r, g, b, a),
+            KeyValueData::UInt64(value) => value.fmt(f),
+        }
+    }
+}
+
+fn write_quoted(f: &mut Formatter, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[test]
+fn test_key_values_roundtrip() {
+    crate::test_roundtrip_write(KeyValues::default());
+    crate::test_roundtrip_write(KeyValues {
+        entries: vec![KeyValue {
+            key: "menu".into(),
+            value: KeyValueData::SubKeys(KeyValues {
+                entries: vec![
+                    KeyValue {
+                        key: "title".into(),
+                        value: KeyValueData::String("Vote".into()),
+                    },
+                    KeyValue {
+                        key: "level".into(),
+                        value: KeyValueData::Int(-2),
+                    },
+                    KeyValue {
+                        key: "time".into(),
+                        value: KeyValueData::Float(20.0),
+                    },
+                    KeyValue {
+                        key: "color".into(),
+                        value: KeyValueData::Color([255, 0, 0, 255]),
+                    },
+                    KeyValue {
+                        key: "wide".into(),
+                        value: KeyValueData::WString,
+                    },
+                    KeyValue {
+                        key: "steamid".into(),
+                        value: KeyValueData::UInt64(76561197960265728),
+                    },
+                    KeyValue {
+                        key: "1".into(),
+                        value: KeyValueData::SubKeys(KeyValues {
+                            entries: vec![KeyValue {
+                                key: "msg".into(),
+                                value: KeyValueData::String("Yes".into()),
+                            }],
+                        }),
+                    },
+                ],
+            }),
+        }],
+    });
+}
+
+#[test]
+fn test_key_values_max_depth() {
+    use bitbuffer::BitReadBuffer;
+
+    let nested = |depth: usize| {
+        let mut data = Vec::new();
+        for _ in 0..depth {
+            data.extend_from_slice(&[TYPE_NONE, b'a', 0]);
+        }
+        data.extend(std::iter::repeat(TYPE_END).take(depth + 1));
+        Stream::new(BitReadBuffer::new_owned(data, LittleEndian))
+    };
+
+    assert!(nested(MAX_DEPTH).read::<KeyValues>().is_ok());
+    assert!(nested(MAX_DEPTH + 1).read::<KeyValues>().is_err());
+}
+
+#[test]
+fn test_key_values_text() {
+    let key_values = KeyValues {
+        entries: vec![KeyValue {
+            key: "MVM_Upgrade".into(),
+            value: KeyValueData::SubKeys(KeyValues {
+                entries: vec![
+                    KeyValue {
+                        key: "Upgrade".into(),
+                        value: KeyValueData::SubKeys(KeyValues {
+                            entries: vec![
+                                KeyValue {
+                                    key: "itemslot".into(),
+                                    value: KeyValueData::Int(0),
+                                },
+                                KeyValue {
+                                    key: "Upgrade".into(),
+                                    value: KeyValueData::Int(12),
+                                },
+                            ],
+                        }),
+                    },
+                    KeyValue {
+                        key: "name".into(),
+                        value: KeyValueData::String("say \"hi\"".into()),
+                    },
+                ],
+            }),
+        }],
+    };
+    assert_eq!(
+        "\"MVM_Upgrade\"\n{\n\t\"Upgrade\"\n\t{\n\t\t\"itemslot\"\t\"0\"\n\t\t\"Upgrade\"\t\"12\"\n\t}\n\t\"name\"\t\"say \\\"hi\\\"\"\n}\n",
+        key_values.to_string()
+    );
+}
@@ -1,4 +1,8 @@
+pub mod keyvalues;
+pub mod model;
+pub mod steamid;
 pub mod userinfo;
+pub mod weapon;
 
 use bitbuffer::{BitRead, BitReadStream, BitWrite, BitWriteStream, Endianness};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -6,7 +10,11 @@ use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Add, Sub};
 
+pub use keyvalues::{KeyValue, KeyValueData, KeyValues};
+pub use model::{ModelType, PickupSize};
+pub use steamid::{SteamId, SteamIdFormat, SteamIdFormatted};
 pub use userinfo::UserInfo;
+pub use weapon::{DamageType, WeaponInfo, WeaponSlot};
 
 #[derive(Eq, PartialEq, Clone)]
 pub enum MaybeUtf8String {
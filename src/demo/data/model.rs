@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+/// Size of a health kit or ammo pack
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PickupSize {
+    Small,
+    Medium,
+    Full,
+}
+
+impl PickupSize {
+    fn from_path(path: &str) -> Option<Self> {
+        if path.contains("_small") {
+            Some(PickupSize::Small)
+        } else if path.contains("_medium") {
+            Some(PickupSize::Medium)
+        } else if path.contains("_large") {
+            Some(PickupSize::Full)
+        } else {
+            None
+        }
+    }
+
+    /// The approximate fraction of the player's max health or ammo given by the pickup
+    pub fn fraction(&self) -> f32 {
+        match self {
+            PickupSize::Small => 0.2,
+            PickupSize::Medium => 0.5,
+            PickupSize::Full => 1.0,
+        }
+    }
+}
+
+/// Classification of a model based on its path in the `modelprecache` table
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelType {
+    /// The map itself
+    World,
+    /// Brush entities that are part of the map such as doors and func_ entities
+    Brush,
+    HealthKit(PickupSize),
+    AmmoPack(PickupSize),
+    Player,
+    Weapon,
+    /// Hats, misc cosmetics and other wearables
+    Cosmetic,
+    Building,
+    /// Props placed in the map
+    Prop,
+    Other,
+}
+
+impl ModelType {
+    pub fn new(path: &str) -> Self {
+        let path = path.to_ascii_lowercase().replace('\\', "/");
+        if path.starts_with("maps/") && path.ends_with(".bsp") {
+            ModelType::World
+        } else if path.starts_with('*') {
+            ModelType::Brush
+        } else if path.contains("medkit") {
+            ModelType::HealthKit(PickupSize::from_path(&path).unwrap_or(PickupSize::Medium))
+        } else if path.starts_with("models/items/plate") {
+            // dropped sandviches heal like a medium health kit
+            ModelType::HealthKit(PickupSize::Medium)
+        } else if path.contains("ammopack") {
+            ModelType::AmmoPack(PickupSize::from_path(&path).unwrap_or(PickupSize::Medium))
+        } else if path.contains("/player/items/") {
+            ModelType::Cosmetic
+        } else if path.starts_with("models/weapons/") {
+            ModelType::Weapon
+        } else if path.starts_with("models/buildables/") {
+            ModelType::Building
+        } else if path.contains("/gibs/") {
+            ModelType::Other
+        } else if path.starts_with("models/player/") || path.starts_with("models/bots/") {
+            ModelType::Player
+        } else if path.contains("/props") {
+            ModelType::Prop
+        } else {
+            ModelType::Other
+        }
+    }
+
+    pub fn is_pickup(&self) -> bool {
+        matches!(self, ModelType::HealthKit(_) | ModelType::AmmoPack(_))
+    }
+}
+
+#[test]
+fn test_model_type() {
+    assert_eq!(
+        ModelType::World,
+        ModelType::new("maps/cp_process_final.bsp")
+    );
+    assert_eq!(ModelType::Brush, ModelType::new("*12"));
+    assert_eq!(
+        ModelType::HealthKit(PickupSize::Small),
+        ModelType::new("models/items/medkit_small.mdl")
+    );
+    assert_eq!(
+        ModelType::HealthKit(PickupSize::Full),
+        ModelType::new("models/props_halloween/halloween_medkit_large.mdl")
+    );
+    assert_eq!(
+        ModelType::HealthKit(PickupSize::Medium),
+        ModelType::new("models/items/plate_sandwich_xmas.mdl")
+    );
+    assert_eq!(
+        ModelType::AmmoPack(PickupSize::Medium),
+        ModelType::new("models/items/ammopack_medium_bday.mdl")
+    );
+    assert_eq!(
+        ModelType::Cosmetic,
+        ModelType::new("models/player/items/soldier/soldier_hat.mdl")
+    );
+    assert_eq!(
+        ModelType::Cosmetic,
+        ModelType::new("models/workshop/player/items/all_class/hwn_spellbook/hwn_spellbook.mdl")
+    );
+    assert_eq!(
+        ModelType::Weapon,
+        ModelType::new("models/weapons/c_models/c_rocketlauncher/c_rocketlauncher.mdl")
+    );
+    assert_eq!(ModelType::Player, ModelType::new("models/player/scout.mdl"));
+    assert_eq!(
+        ModelType::Building,
+        ModelType::new("models/buildables/sentry1.mdl")
+    );
+    assert_eq!(
+        ModelType::Prop,
+        ModelType::new("models/props_gameplay/resupply_locker.mdl")
+    );
+    assert_eq!(
+        ModelType::Other,
+        ModelType::new("models/player/gibs/gibs_duck.mdl")
+    );
+    assert_eq!(ModelType::Other, ModelType::new("sprites/glow01.vmt"));
+}
@@ -0,0 +1,315 @@
+use bitbuffer::{BitWriteSized, BitWriteStream, Endianness};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use steamid_ng::SteamID;
+
+/// Steam id of a user as stored in the `userinfo` string table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SteamId {
+    User(SteamID),
+    Bot,
+    SourceTv,
+    /// Steam ids that can't be parsed, like `STEAM_ID_PENDING` or `STEAM_ID_LAN`
+    Unknown(String),
+}
+
+impl Default for SteamId {
+    fn default() -> Self {
+        SteamId::Unknown(String::new())
+    }
+}
+
+impl SteamId {
+    /// Parse the raw steam id from a player info entry
+    ///
+    /// SourceTV uses the same `BOT` steam id as regular bots, so the hltv flag is needed to tell them apart
+    pub fn from_raw(raw: &str, is_hltv: bool) -> Self {
+        match raw.parse() {
+            Ok(SteamId::Bot) if is_hltv => SteamId::SourceTv,
+            Ok(steam_id) => steam_id,
+            Err(_) => SteamId::Unknown(raw.into()),
+        }
+    }
+
+    pub fn steam_id(&self) -> Option<SteamID> {
+        match self {
+            SteamId::User(steam_id) => Some(*steam_id),
+            _ => None,
+        }
+    }
+
+    pub fn account_id(&self) -> Option<u32> {
+        self.steam_id().map(|steam_id| steam_id.account_id())
+    }
+
+    /// Steam id in the 64 bit `76561197960287930` format
+    pub fn steam64(&self) -> Option<u64> {
+        self.steam_id().map(u64::from)
+    }
+
+    /// Steam id in the `[U:1:22202]` format
+    pub fn steam3(&self) -> Option<String> {
+        self.steam_id().map(|steam_id| steam_id.steam3())
+    }
+
+    /// Steam id in the `STEAM_1:0:11101` format
+    pub fn steam2(&self) -> Option<String> {
+        self.steam_id().map(|steam_id| steam_id.steam2())
+    }
+
+    pub fn is_bot(&self) -> bool {
+        matches!(self, SteamId::Bot)
+    }
+
+    pub fn is_source_tv(&self) -> bool {
+        matches!(self, SteamId::SourceTv)
+    }
+
+    /// Format the steam id in the given format, non-user steam ids are formatted as they appear in the demo
+    pub fn format(&self, format: SteamIdFormat) -> String {
+        match (self, format) {
+            (SteamId::User(steam_id), SteamIdFormat::Steam3) => steam_id.steam3(),
+            (SteamId::User(steam_id), SteamIdFormat::Steam2) => steam_id.steam2(),
+            (SteamId::User(steam_id), SteamIdFormat::Steam64) => u64::from(*steam_id).to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl FromStr for SteamId {
+    type Err = steamid_ng::SteamIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BOT" => Ok(SteamId::Bot),
+            "SourceTV" => Ok(SteamId::SourceTv),
+            _ => SteamID::try_from(s).map(SteamId::User),
+        }
+    }
+}
+
+impl Display for SteamId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SteamId::User(steam_id) => write!(f, "{}", steam_id.steam3()),
+            SteamId::Bot => write!(f, "BOT"),
+            SteamId::SourceTv => write!(f, "SourceTV"),
+            SteamId::Unknown(raw) => write!(f, "{}", raw),
+        }
+    }
+}
+
+impl From<SteamID> for SteamId {
+    fn from(steam_id: SteamID) -> Self {
+        SteamId::User(steam_id)
+    }
+}
+
+impl SteamId {
+    /// Format the steam id as stored in a player info entry
+    ///
+    /// If the steam id is unchanged from the `original` raw steam id, the original is returned as is,
+    /// otherwise the steam id is formatted in the same format as the original.
+    pub fn to_raw(&self, original: &str) -> String {
+        if SteamId::from_raw(original, self.is_source_tv()) == *self {
+            return original.into();
+        }
+        match self {
+            SteamId::User(steam_id) if original.starts_with("STEAM_0:") => {
+                steam_id.steam2().replacen("STEAM_1:", "STEAM_0:", 1)
+            }
+            SteamId::User(steam_id) if original.starts_with("STEAM_") => steam_id.steam2(),
+            SteamId::SourceTv => "BOT".into(),
+            steam_id => steam_id.to_string(),
+        }
+    }
+}
+
+impl<E: Endianness> BitWriteSized<E> for SteamId {
+    fn write_sized(&self, stream: &mut BitWriteStream<E>, len: usize) -> bitbuffer::Result<()> {
+        self.to_raw("").write_sized(stream, len)
+    }
+}
+
+/// Format used when serializing steam ids
+///
+/// Steam ids are serialized in the steam3 format unless the value is wrapped using [`SteamIdFormat::formatted`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SteamIdFormat {
+    #[default]
+    Steam3,
+    Steam2,
+    Steam64,
+}
+
+thread_local! {
+    static SERIALIZE_FORMAT: Cell<SteamIdFormat> = const { Cell::new(SteamIdFormat::Steam3) };
+}
+
+impl SteamIdFormat {
+    /// Wrap a value to serialize all steam ids contained in it in this format
+    ///
+    /// ```
+    /// # use tf_demo_parser::demo::data::{SteamId, SteamIdFormat};
+    /// let steam_id: SteamId = "[U:1:22202]".parse().unwrap();
+    /// let json = serde_json::to_string(&SteamIdFormat::Steam64.formatted(&steam_id)).unwrap();
+    /// assert_eq!(r#""76561197960287930""#, json);
+    /// ```
+    pub fn formatted<T: Serialize + ?Sized>(self, value: &T) -> SteamIdFormatted<'_, T> {
+        SteamIdFormatted {
+            value,
+            format: self,
+        }
+    }
+}
+
+/// A value that serializes the steam ids contained in it in the chosen format
+///
+/// Created by [`SteamIdFormat::formatted`]
+pub struct SteamIdFormatted<'a, T: ?Sized> {
+    value: &'a T,
+    format: SteamIdFormat,
+}
+
+impl<T: Serialize + ?Sized> Serialize for SteamIdFormatted<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        /// Restore the previous format, also when serialization panics
+        struct Restore(SteamIdFormat);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SERIALIZE_FORMAT.with(|format| format.set(self.0));
+            }
+        }
+
+        let _restore = Restore(SERIALIZE_FORMAT.with(|format| format.replace(self.format)));
+        self.value.serialize(serializer)
+    }
+}
+
+impl FromStr for SteamIdFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "steam3" => Ok(SteamIdFormat::Steam3),
+            "steam2" => Ok(SteamIdFormat::Steam2),
+            "steam64" => Ok(SteamIdFormat::Steam64),
+            _ => Err("unknown steam id format, expected one of steam3, steam2 or steam64"),
+        }
+    }
+}
+
+impl Serialize for SteamId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.format(SERIALIZE_FORMAT.with(Cell::get))
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SteamId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum IntOrStr {
+            Int(u64),
+            Str(String),
+        }
+
+        Ok(match IntOrStr::deserialize(deserializer)? {
+            IntOrStr::Int(steam_id) => SteamId::User(steam_id.into()),
+            IntOrStr::Str(raw) => raw.parse().unwrap_or(SteamId::Unknown(raw)),
+        })
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for SteamId {
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[test]
+fn test_steam_id_parse() {
+    let steam_id = SteamId::from_raw("[U:1:22202]", false);
+    assert_eq!(Some(76561197960287930), steam_id.steam64());
+    assert_eq!(Some("STEAM_1:0:11101".to_string()), steam_id.steam2());
+    assert_eq!(Some("[U:1:22202]".to_string()), steam_id.steam3());
+    assert_eq!(Some(22202), steam_id.account_id());
+
+    assert_eq!(SteamId::Bot, SteamId::from_raw("BOT", false));
+    assert_eq!(SteamId::SourceTv, SteamId::from_raw("BOT", true));
+    assert_eq!(
+        SteamId::Unknown("STEAM_ID_PENDING".into()),
+        SteamId::from_raw("STEAM_ID_PENDING", false)
+    );
+}
+
+#[test]
+fn test_steam_id_serde() {
+    let steam_id = SteamId::from_raw("[U:1:22202]", false);
+    assert_eq!(
+        r#""[U:1:22202]""#,
+        serde_json::to_string(&steam_id).unwrap()
+    );
+    assert_eq!("76561197960287930", steam_id.format(SteamIdFormat::Steam64));
+    assert_eq!(
+        r#"["STEAM_1:0:11101","BOT"]"#,
+        serde_json::to_string(&SteamIdFormat::Steam2.formatted(&[steam_id.clone(), SteamId::Bot]))
+            .unwrap()
+    );
+    // the format only applies to the wrapped value
+    assert_eq!(
+        r#""[U:1:22202]""#,
+        serde_json::to_string(&steam_id).unwrap()
+    );
+    assert_eq!(
+        steam_id,
+        serde_json::from_str(r#""76561197960287930""#).unwrap()
+    );
+    assert_eq!(
+        steam_id,
+        serde_json::from_str(r#""STEAM_1:0:11101""#).unwrap()
+    );
+    assert_eq!(steam_id, serde_json::from_str("76561197960287930").unwrap());
+    assert_eq!(
+        SteamId::SourceTv,
+        serde_json::from_str(r#""SourceTV""#).unwrap()
+    );
+}
+
+#[test]
+fn test_steam_id_to_raw() {
+    let steam_id = SteamId::from_raw("STEAM_0:0:11101", false);
+    assert_eq!(Some(22202), steam_id.account_id());
+    assert_eq!("STEAM_0:0:11101", steam_id.to_raw("STEAM_0:0:11101"));
+    assert_eq!("STEAM_1:0:11101", steam_id.to_raw("STEAM_1:0:11101"));
+    assert_eq!("[U:1:22202]", steam_id.to_raw("[U:1:22202]"));
+
+    // changed steam ids keep the format of the original
+    let other = SteamId::from_raw("[U:1:64229260]", false);
+    assert_eq!("STEAM_0:0:32114630", other.to_raw("STEAM_0:0:11101"));
+    assert_eq!("STEAM_1:0:32114630", other.to_raw("STEAM_1:0:11101"));
+    assert_eq!("[U:1:64229260]", other.to_raw("[U:1:22202]"));
+    assert_eq!("[U:1:64229260]", other.to_raw(""));
+
+    assert_eq!("BOT", SteamId::SourceTv.to_raw("BOT"));
+    assert_eq!("BOT", SteamId::SourceTv.to_raw(""));
+}
@@ -1,8 +1,11 @@
+use crate::demo::data::SteamId;
 use crate::demo::message::packetentities::EntityId;
 use crate::demo::packet::stringtable::{ExtraData, StringTableEntry};
 use crate::demo::parser::analyser::UserId;
 use crate::{ReadResult, Stream};
-use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, LittleEndian};
+use bitbuffer::{
+    BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteSized, BitWriteStream, LittleEndian,
+};
 
 #[derive(BitRead, Debug)]
 struct RawPlayerInfo {
@@ -21,13 +24,13 @@ struct RawPlayerInfo {
     pub more_extra: u8,
 }
 
-#[derive(BitWrite, Debug, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct PlayerInfo {
-    #[size = 32]
     pub name: String,
     pub user_id: UserId,
-    #[size = 32]
-    pub steam_id: String,
+    pub steam_id: SteamId,
+    /// The steam id as stored in the demo, used to write unchanged steam ids back in their original format
+    pub raw_steam_id: String,
     pub extra: u32, // all my sources say these 4 bytes don't exist
     pub friends_id: u32,
     pub friends_name_bytes: [u8; 32], // seem to all be 0 now
@@ -46,7 +49,8 @@ impl From<RawPlayerInfo> for PlayerInfo {
                 .trim_end_matches('\0')
                 .to_string(),
             user_id: raw.user_id.into(),
-            steam_id: raw.steam_id,
+            steam_id: SteamId::from_raw(&raw.steam_id, raw.is_hl_tv > 0),
+            raw_steam_id: raw.steam_id,
             extra: raw.extra,
             friends_id: raw.friends_id,
             friends_name_bytes: raw.friends_name_bytes,
@@ -60,6 +64,25 @@ impl From<RawPlayerInfo> for PlayerInfo {
     }
 }
 
+impl BitWrite<LittleEndian> for PlayerInfo {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.name.write_sized(stream, 32)?;
+        self.user_id.write(stream)?;
+        self.steam_id
+            .to_raw(&self.raw_steam_id)
+            .write_sized(stream, 32)?;
+        self.extra.write(stream)?;
+        self.friends_id.write(stream)?;
+        self.friends_name_bytes.write(stream)?;
+        self.is_fake_player.write(stream)?;
+        self.is_hl_tv.write(stream)?;
+        self.is_replay.write(stream)?;
+        self.custom_file.write(stream)?;
+        self.files_downloaded.write(stream)?;
+        self.more_extra.write(stream)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct UserInfo {
     pub entity_id: EntityId,
@@ -92,7 +115,8 @@ impl UserInfo {
     }
 
     pub fn encode_to_string_table(&self) -> ReadResult<StringTableEntry<'static>> {
-        let text = format!("{}", self.entity_id);
+        // the entity id is stored as the player slot, one lower than the entity id
+        let text = format!("{}", u32::from(self.entity_id).saturating_sub(1));
         let mut extra_data = Vec::with_capacity(132);
         {
             let mut stream = BitWriteStream::new(&mut extra_data, LittleEndian);
@@ -107,3 +131,58 @@ impl UserInfo {
         })
     }
 }
+
+#[test]
+fn test_user_info_string_table_roundtrip() {
+    let info = UserInfo {
+        entity_id: 3u32.into(),
+        player_info: PlayerInfo {
+            name: "player".into(),
+            user_id: 12u32.into(),
+            steam_id: "[U:1:64229260]".parse().unwrap(),
+            ..PlayerInfo::default()
+        },
+    };
+    let entry = info.encode_to_string_table().unwrap();
+    let parsed = UserInfo::parse_from_string_table(
+        2,
+        entry.text.as_deref(),
+        entry.extra_data.map(|extra| extra.data),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(info.entity_id, parsed.entity_id);
+    assert_eq!(info.player_info.name, parsed.player_info.name);
+    assert_eq!(info.player_info.user_id, parsed.player_info.user_id);
+    assert_eq!(info.player_info.steam_id, parsed.player_info.steam_id);
+}
+
+#[test]
+fn test_user_info_steam2_roundtrip() {
+    let mut data = Vec::new();
+    {
+        let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+        [0u8; 32].write(&mut stream).unwrap();
+        12u32.write(&mut stream).unwrap();
+        "STEAM_0:0:11101".write_sized(&mut stream, 32).unwrap();
+        [0u8; 64].write(&mut stream).unwrap();
+    }
+    let info = UserInfo::parse_from_string_table(
+        2,
+        Some("2"),
+        Some(Stream::new(BitReadBuffer::new_owned(
+            data.clone(),
+            LittleEndian,
+        ))),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(Some(22202), info.player_info.steam_id.account_id());
+
+    let entry = info.encode_to_string_table().unwrap();
+    let mut encoded = entry.extra_data.unwrap().data;
+    assert_eq!(
+        data,
+        encoded.read_bytes(encoded.bit_len() / 8).unwrap().to_vec()
+    );
+}
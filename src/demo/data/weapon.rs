@@ -0,0 +1,311 @@
+use crate::demo::parser::analyser::Class;
+use serde::Serialize;
+use DamageType::{Blast, Bullet, Fire, Melee, Projectile};
+use WeaponSlot::{Building, Primary, Secondary, Taunt};
+
+/// The loadout slot a weapon is equipped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WeaponSlot {
+    Primary,
+    Secondary,
+    Melee,
+    /// Sentry guns and other buildings
+    Building,
+    Taunt,
+    /// Kills not caused by a weapon such as falling or telefrags
+    Other,
+}
+
+/// The main type of damage dealt by a weapon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DamageType {
+    Bullet,
+    Blast,
+    Fire,
+    Melee,
+    /// Non explosive projectiles like arrows, syringes and energy projectiles
+    Projectile,
+    Other,
+}
+
+/// Metadata for the weapon belonging to a kill icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponInfo {
+    /// The kill icon as found in the `weapon` of a player death
+    pub kill_icon: &'static str,
+    pub name: &'static str,
+    /// The class that uses the weapon, [`Class::Other`] for multi-class weapons and world kills
+    pub class: Class,
+    pub slot: WeaponSlot,
+    pub damage_type: DamageType,
+    /// Item definition indices of the weapon, including reskins that share the kill icon
+    pub item_indices: &'static [u32],
+}
+
+impl WeaponInfo {
+    /// Find the weapon info for a kill icon
+    pub fn from_kill_icon(kill_icon: &str) -> Option<&'static WeaponInfo> {
+        WEAPONS.iter().find(|weapon| weapon.kill_icon == kill_icon)
+    }
+
+    /// Find the weapon info for an item definition index
+    pub fn from_item_index(index: u32) -> Option<&'static WeaponInfo> {
+        WEAPONS
+            .iter()
+            .find(|weapon| weapon.item_indices.contains(&index))
+    }
+}
+
+const fn weapon(
+    kill_icon: &'static str,
+    name: &'static str,
+    class: Class,
+    slot: WeaponSlot,
+    damage_type: DamageType,
+    item_indices: &'static [u32],
+) -> WeaponInfo {
+    WeaponInfo {
+        kill_icon,
+        name,
+        class,
+        slot,
+        damage_type,
+        item_indices,
+    }
+}
+
+#[rustfmt::skip]
+static WEAPONS: &[WeaponInfo] = &[
+    // scout
+    weapon("scattergun", "Scattergun", Class::Scout, Primary, Bullet, &[13, 200, 669, 799]),
+    weapon("force_a_nature", "Force-A-Nature", Class::Scout, Primary, Bullet, &[45, 1078]),
+    weapon("shortstop", "Shortstop", Class::Scout, Primary, Bullet, &[220]),
+    weapon("soda_popper", "Soda Popper", Class::Scout, Primary, Bullet, &[448]),
+    weapon("pep_brawlerblaster", "Baby Face's Blaster", Class::Scout, Primary, Bullet, &[772]),
+    weapon("back_scatter", "Back Scatter", Class::Scout, Primary, Bullet, &[1103]),
+    weapon("pistol_scout", "Pistol", Class::Scout, Secondary, Bullet, &[23, 209]),
+    weapon("maxgun", "Lugermorph", Class::Scout, Secondary, Bullet, &[160, 294]),
+    weapon("the_winger", "Winger", Class::Scout, Secondary, Bullet, &[449]),
+    weapon("pep_pistol", "Pretty Boy's Pocket Pistol", Class::Scout, Secondary, Bullet, &[773]),
+    weapon("guillotine", "Flying Guillotine", Class::Scout, Secondary, Projectile, &[812, 833]),
+    weapon("bat", "Bat", Class::Scout, WeaponSlot::Melee, Melee, &[0, 190, 660]),
+    weapon("sandman", "Sandman", Class::Scout, WeaponSlot::Melee, Melee, &[44]),
+    weapon("ball", "Sandman Ball", Class::Scout, WeaponSlot::Melee, Projectile, &[44]),
+    weapon("holy_mackerel", "Holy Mackerel", Class::Scout, WeaponSlot::Melee, Melee, &[221, 999]),
+    weapon("candy_cane", "Candy Cane", Class::Scout, WeaponSlot::Melee, Melee, &[317]),
+    weapon("boston_basher", "Boston Basher", Class::Scout, WeaponSlot::Melee, Melee, &[325]),
+    weapon("lava_bat", "Sun-on-a-Stick", Class::Scout, WeaponSlot::Melee, Melee, &[349]),
+    weapon("warfan", "Fan O'War", Class::Scout, WeaponSlot::Melee, Melee, &[355]),
+    weapon("atomizer", "Atomizer", Class::Scout, WeaponSlot::Melee, Melee, &[450]),
+    weapon("wrap_assassin", "Wrap Assassin", Class::Scout, WeaponSlot::Melee, Melee, &[648]),
+    weapon("unarmed_combat", "Unarmed Combat", Class::Scout, WeaponSlot::Melee, Melee, &[572]),
+    weapon("taunt_scout", "Home Run", Class::Scout, Taunt, Melee, &[44]),
+    // soldier
+    weapon("tf_projectile_rocket", "Rocket Launcher", Class::Soldier, Primary, Blast, &[18, 205, 658, 800]),
+    weapon("quake_rl", "Original", Class::Soldier, Primary, Blast, &[513]),
+    weapon("blackbox", "Black Box", Class::Soldier, Primary, Blast, &[228, 1085]),
+    weapon("rocketlauncher_directhit", "Direct Hit", Class::Soldier, Primary, Blast, &[127]),
+    weapon("liberty_launcher", "Liberty Launcher", Class::Soldier, Primary, Blast, &[414]),
+    weapon("cow_mangler", "Cow Mangler 5000", Class::Soldier, Primary, Blast, &[441]),
+    weapon("dumpster_device", "Beggar's Bazooka", Class::Soldier, Primary, Blast, &[730]),
+    weapon("airstrike", "Air Strike", Class::Soldier, Primary, Blast, &[1104]),
+    weapon("shotgun_soldier", "Shotgun", Class::Soldier, Secondary, Bullet, &[10, 199]),
+    weapon("righteous_bison", "Righteous Bison", Class::Soldier, Secondary, Projectile, &[442]),
+    weapon("reserve_shooter", "Reserve Shooter", Class::Soldier, Secondary, Bullet, &[415]),
+    weapon("panic_attack", "Panic Attack", Class::Other, Secondary, Bullet, &[1153]),
+    weapon("mantreads", "Mantreads", Class::Soldier, Secondary, Melee, &[444]),
+    weapon("shovel", "Shovel", Class::Soldier, WeaponSlot::Melee, Melee, &[6, 196]),
+    weapon("unique_pickaxe", "Equalizer", Class::Soldier, WeaponSlot::Melee, Melee, &[128]),
+    weapon("unique_pickaxe_escape", "Escape Plan", Class::Soldier, WeaponSlot::Melee, Melee, &[775]),
+    weapon("market_gardener", "Market Gardener", Class::Soldier, WeaponSlot::Melee, Melee, &[416]),
+    weapon("disciplinary_action", "Disciplinary Action", Class::Soldier, WeaponSlot::Melee, Melee, &[447]),
+    weapon("demokatana", "Half-Zatoichi", Class::Other, WeaponSlot::Melee, Melee, &[357]),
+    weapon("paintrain", "Pain Train", Class::Other, WeaponSlot::Melee, Melee, &[154]),
+    weapon("taunt_soldier", "Kamikaze", Class::Soldier, Taunt, Blast, &[]),
+    // pyro
+    weapon("flamethrower", "Flame Thrower", Class::Pyro, Primary, Fire, &[21, 208, 659, 798]),
+    weapon("backburner", "Backburner", Class::Pyro, Primary, Fire, &[40, 1146]),
+    weapon("degreaser", "Degreaser", Class::Pyro, Primary, Fire, &[215]),
+    weapon("phlogistinator", "Phlogistinator", Class::Pyro, Primary, Fire, &[594]),
+    weapon("rainblower", "Rainblower", Class::Pyro, Primary, Fire, &[741]),
+    weapon("dragons_fury", "Dragon's Fury", Class::Pyro, Primary, Fire, &[1178]),
+    weapon("deflect_rocket", "Deflected Rocket", Class::Pyro, Primary, Blast, &[]),
+    weapon("deflect_promode", "Deflected Grenade", Class::Pyro, Primary, Blast, &[]),
+    weapon("deflect_sticky", "Deflected Stickybomb", Class::Pyro, Primary, Blast, &[]),
+    weapon("deflect_arrow", "Deflected Arrow", Class::Pyro, Primary, Projectile, &[]),
+    weapon("deflect_flare", "Deflected Flare", Class::Pyro, Primary, Fire, &[]),
+    weapon("shotgun_pyro", "Shotgun", Class::Pyro, Secondary, Bullet, &[12]),
+    weapon("flaregun", "Flare Gun", Class::Pyro, Secondary, Fire, &[39, 1081]),
+    weapon("detonator", "Detonator", Class::Pyro, Secondary, Fire, &[351]),
+    weapon("manmelter", "Manmelter", Class::Pyro, Secondary, Fire, &[595]),
+    weapon("scorch_shot", "Scorch Shot", Class::Pyro, Secondary, Fire, &[740]),
+    weapon("rocketpack_stomp", "Thermal Thruster", Class::Pyro, Secondary, Melee, &[1179]),
+    weapon("fireaxe", "Fire Axe", Class::Pyro, WeaponSlot::Melee, Melee, &[2, 192]),
+    weapon("axtinguisher", "Axtinguisher", Class::Pyro, WeaponSlot::Melee, Melee, &[38, 1000]),
+    weapon("sledgehammer", "Homewrecker", Class::Pyro, WeaponSlot::Melee, Melee, &[153]),
+    weapon("powerjack", "Powerjack", Class::Pyro, WeaponSlot::Melee, Melee, &[214]),
+    weapon("back_scratcher", "Back Scratcher", Class::Pyro, WeaponSlot::Melee, Melee, &[326]),
+    weapon("thirddegree", "Third Degree", Class::Pyro, WeaponSlot::Melee, Melee, &[593]),
+    weapon("hot_hand", "Hot Hand", Class::Pyro, WeaponSlot::Melee, Melee, &[1181]),
+    weapon("taunt_pyro", "Hadouken", Class::Pyro, Taunt, Fire, &[]),
+    // demoman
+    weapon("tf_projectile_pipe", "Grenade Launcher", Class::Demoman, Primary, Blast, &[19, 206, 1007]),
+    weapon("loch_n_load", "Loch-n-Load", Class::Demoman, Primary, Blast, &[308]),
+    weapon("loose_cannon", "Loose Cannon", Class::Demoman, Primary, Blast, &[996]),
+    weapon("loose_cannon_impact", "Loose Cannon", Class::Demoman, Primary, Blast, &[996]),
+    weapon("iron_bomber", "Iron Bomber", Class::Demoman, Primary, Blast, &[1151]),
+    weapon("tf_projectile_pipe_remote", "Stickybomb Launcher", Class::Demoman, Secondary, Blast, &[20, 207, 661, 797]),
+    weapon("sticky_resistance", "Scottish Resistance", Class::Demoman, Secondary, Blast, &[130]),
+    weapon("quickiebomb_launcher", "Quickiebomb Launcher", Class::Demoman, Secondary, Blast, &[1150]),
+    weapon("demoshield", "Chargin' Targe", Class::Demoman, Secondary, Melee, &[131, 1144]),
+    weapon("splendid_screen", "Splendid Screen", Class::Demoman, Secondary, Melee, &[406]),
+    weapon("tide_turner", "Tide Turner", Class::Demoman, Secondary, Melee, &[1099]),
+    weapon("bottle", "Bottle", Class::Demoman, WeaponSlot::Melee, Melee, &[1, 191]),
+    weapon("sword", "Eyelander", Class::Demoman, WeaponSlot::Melee, Melee, &[132, 1082]),
+    weapon("headtaker", "Horseless Headless Horsemann's Headtaker", Class::Demoman, WeaponSlot::Melee, Melee, &[266]),
+    weapon("nessieclub", "Nessie's Nine Iron", Class::Demoman, WeaponSlot::Melee, Melee, &[482]),
+    weapon("claidheamohmor", "Claidheamh Mòr", Class::Demoman, WeaponSlot::Melee, Melee, &[327]),
+    weapon("persian_persuader", "Persian Persuader", Class::Demoman, WeaponSlot::Melee, Melee, &[404]),
+    weapon("battleaxe", "Scotsman's Skullcutter", Class::Demoman, WeaponSlot::Melee, Melee, &[172]),
+    weapon("ullapool_caber", "Ullapool Caber", Class::Demoman, WeaponSlot::Melee, Melee, &[307]),
+    weapon("ullapool_caber_explosion", "Ullapool Caber", Class::Demoman, WeaponSlot::Melee, Blast, &[307]),
+    weapon("taunt_demoman", "Decapitation", Class::Demoman, Taunt, Melee, &[]),
+    // heavy
+    weapon("minigun", "Minigun", Class::Heavy, Primary, Bullet, &[15, 202, 654, 793]),
+    weapon("natascha", "Natascha", Class::Heavy, Primary, Bullet, &[41]),
+    weapon("brass_beast", "Brass Beast", Class::Heavy, Primary, Bullet, &[312]),
+    weapon("tomislav", "Tomislav", Class::Heavy, Primary, Bullet, &[424]),
+    weapon("long_heatmaker", "Huo-Long Heater", Class::Heavy, Primary, Bullet, &[811, 832]),
+    weapon("iron_curtain", "Iron Curtain", Class::Heavy, Primary, Bullet, &[298]),
+    weapon("shotgun_hwg", "Shotgun", Class::Heavy, Secondary, Bullet, &[11]),
+    weapon("family_business", "Family Business", Class::Heavy, Secondary, Bullet, &[425]),
+    weapon("fists", "Fists", Class::Heavy, WeaponSlot::Melee, Melee, &[5, 195]),
+    weapon("gloves", "Killing Gloves of Boxing", Class::Heavy, WeaponSlot::Melee, Melee, &[43]),
+    weapon("gloves_running_urgently", "Gloves of Running Urgently", Class::Heavy, WeaponSlot::Melee, Melee, &[239, 1084]),
+    weapon("warrior_spirit", "Warrior's Spirit", Class::Heavy, WeaponSlot::Melee, Melee, &[310]),
+    weapon("steel_fists", "Fists of Steel", Class::Heavy, WeaponSlot::Melee, Melee, &[331]),
+    weapon("eviction_notice", "Eviction Notice", Class::Heavy, WeaponSlot::Melee, Melee, &[426]),
+    weapon("apocofists", "Apoco-Fists", Class::Heavy, WeaponSlot::Melee, Melee, &[587]),
+    weapon("holiday_punch", "Holiday Punch", Class::Heavy, WeaponSlot::Melee, Melee, &[656]),
+    weapon("bread_bite", "Bread Bite", Class::Heavy, WeaponSlot::Melee, Melee, &[1100]),
+    weapon("taunt_heavy", "Showdown", Class::Heavy, Taunt, Bullet, &[]),
+    // engineer
+    weapon("shotgun_primary", "Shotgun", Class::Engineer, Primary, Bullet, &[9]),
+    weapon("frontier_justice", "Frontier Justice", Class::Engineer, Primary, Bullet, &[141, 1004]),
+    weapon("widowmaker", "Widowmaker", Class::Engineer, Primary, Bullet, &[527]),
+    weapon("pomson", "Pomson 6000", Class::Engineer, Primary, Projectile, &[588]),
+    weapon("rescue_ranger", "Rescue Ranger", Class::Engineer, Primary, Projectile, &[997]),
+    weapon("pistol", "Pistol", Class::Engineer, Secondary, Bullet, &[22]),
+    weapon("short_circuit", "Short Circuit", Class::Engineer, Secondary, Projectile, &[528]),
+    weapon("wrangler_kill", "Wrangler", Class::Engineer, Secondary, Bullet, &[140, 1086]),
+    weapon("wrench", "Wrench", Class::Engineer, WeaponSlot::Melee, Melee, &[7, 197, 662, 795]),
+    weapon("robot_arm", "Gunslinger", Class::Engineer, WeaponSlot::Melee, Melee, &[142]),
+    weapon("robot_arm_combo_kill", "Gunslinger", Class::Engineer, WeaponSlot::Melee, Melee, &[142]),
+    weapon("southern_hospitality", "Southern Hospitality", Class::Engineer, WeaponSlot::Melee, Melee, &[155]),
+    weapon("wrench_jag", "Jag", Class::Engineer, WeaponSlot::Melee, Melee, &[329]),
+    weapon("eureka_effect", "Eureka Effect", Class::Engineer, WeaponSlot::Melee, Melee, &[589]),
+    weapon("wrench_golden", "Golden Wrench", Class::Engineer, WeaponSlot::Melee, Melee, &[169]),
+    weapon("obj_sentrygun", "Sentry Gun Level 1", Class::Engineer, Building, Bullet, &[]),
+    weapon("obj_sentrygun2", "Sentry Gun Level 2", Class::Engineer, Building, Bullet, &[]),
+    weapon("obj_sentrygun3", "Sentry Gun Level 3", Class::Engineer, Building, Bullet, &[]),
+    weapon("obj_minisentry", "Combat Mini-Sentry Gun", Class::Engineer, Building, Bullet, &[]),
+    weapon("taunt_guitar_kill", "Guitar Smash", Class::Engineer, Taunt, Melee, &[]),
+    weapon("robot_arm_blender_kill", "Organ Grinder", Class::Engineer, Taunt, Melee, &[]),
+    // medic
+    weapon("syringegun_medic", "Syringe Gun", Class::Medic, Primary, Projectile, &[17, 204]),
+    weapon("blutsauger", "Blutsauger", Class::Medic, Primary, Projectile, &[36]),
+    weapon("crusaders_crossbow", "Crusader's Crossbow", Class::Medic, Primary, Projectile, &[305, 1079]),
+    weapon("proto_syringe", "Overdose", Class::Medic, Primary, Projectile, &[412]),
+    weapon("bonesaw", "Bonesaw", Class::Medic, WeaponSlot::Melee, Melee, &[8, 198, 1143]),
+    weapon("ubersaw", "Ubersaw", Class::Medic, WeaponSlot::Melee, Melee, &[37, 1003]),
+    weapon("battleneedle", "Vita-Saw", Class::Medic, WeaponSlot::Melee, Melee, &[173]),
+    weapon("amputator", "Amputator", Class::Medic, WeaponSlot::Melee, Melee, &[304]),
+    weapon("solemn_vow", "Solemn Vow", Class::Medic, WeaponSlot::Melee, Melee, &[413]),
+    weapon("taunt_medic", "Spinal Tap", Class::Medic, Taunt, Melee, &[]),
+    // sniper
+    weapon("sniperrifle", "Sniper Rifle", Class::Sniper, Primary, Bullet, &[14, 201, 664, 792]),
+    weapon("sydney_sleeper", "Sydney Sleeper", Class::Sniper, Primary, Bullet, &[230]),
+    weapon("bazaar_bargain", "Bazaar Bargain", Class::Sniper, Primary, Bullet, &[402]),
+    weapon("machina", "Machina", Class::Sniper, Primary, Bullet, &[526, 30665]),
+    weapon("player_penetration", "Machina", Class::Sniper, Primary, Bullet, &[526, 30665]),
+    weapon("pro_rifle", "Hitman's Heatmaker", Class::Sniper, Primary, Bullet, &[752]),
+    weapon("the_classic", "Classic", Class::Sniper, Primary, Bullet, &[1098]),
+    weapon("awper_hand", "AWPer Hand", Class::Sniper, Primary, Bullet, &[851]),
+    weapon("tf_projectile_arrow", "Huntsman", Class::Sniper, Primary, Projectile, &[56, 1005, 1092]),
+    weapon("huntsman", "Huntsman", Class::Sniper, Primary, Projectile, &[56, 1005, 1092]),
+    weapon("smg", "SMG", Class::Sniper, Secondary, Bullet, &[16, 203]),
+    weapon("pro_smg", "Cleaner's Carbine", Class::Sniper, Secondary, Bullet, &[751]),
+    weapon("club", "Kukri", Class::Sniper, WeaponSlot::Melee, Melee, &[3, 193]),
+    weapon("tribalkukri", "Tribalman's Shiv", Class::Sniper, WeaponSlot::Melee, Melee, &[171]),
+    weapon("bushwacka", "Bushwacka", Class::Sniper, WeaponSlot::Melee, Melee, &[232]),
+    weapon("shahanshah", "Shahanshah", Class::Sniper, WeaponSlot::Melee, Melee, &[401]),
+    weapon("taunt_sniper", "Skewer", Class::Sniper, Taunt, Projectile, &[]),
+    // spy
+    weapon("revolver", "Revolver", Class::Spy, Secondary, Bullet, &[24, 210, 1142]),
+    weapon("ambassador", "Ambassador", Class::Spy, Secondary, Bullet, &[61, 1006]),
+    weapon("letranger", "L'Etranger", Class::Spy, Secondary, Bullet, &[224]),
+    weapon("enforcer", "Enforcer", Class::Spy, Secondary, Bullet, &[460]),
+    weapon("diamondback", "Diamondback", Class::Spy, Secondary, Bullet, &[525]),
+    weapon("knife", "Knife", Class::Spy, WeaponSlot::Melee, Melee, &[4, 194, 665, 794]),
+    weapon("eternal_reward", "Your Eternal Reward", Class::Spy, WeaponSlot::Melee, Melee, &[225]),
+    weapon("kunai", "Conniver's Kunai", Class::Spy, WeaponSlot::Melee, Melee, &[356]),
+    weapon("big_earner", "Big Earner", Class::Spy, WeaponSlot::Melee, Melee, &[461]),
+    weapon("spy_cicle", "Spy-cicle", Class::Spy, WeaponSlot::Melee, Melee, &[649]),
+    weapon("black_rose", "Black Rose", Class::Spy, WeaponSlot::Melee, Melee, &[727]),
+    weapon("taunt_spy", "Fencing", Class::Spy, Taunt, Melee, &[]),
+    // multi-class
+    weapon("fryingpan", "Frying Pan", Class::Other, WeaponSlot::Melee, Melee, &[264, 1071]),
+    weapon("saxxy", "Saxxy", Class::Other, WeaponSlot::Melee, Melee, &[423]),
+    weapon("nonnonviolent_protest", "Conscientious Objector", Class::Other, WeaponSlot::Melee, Melee, &[474]),
+    weapon("freedom_staff", "Freedom Staff", Class::Other, WeaponSlot::Melee, Melee, &[880]),
+    weapon("bat_outta_hell", "Bat Outta Hell", Class::Other, WeaponSlot::Melee, Melee, &[939]),
+    weapon("memory_maker", "Memory Maker", Class::Other, WeaponSlot::Melee, Melee, &[954]),
+    weapon("ham_shank", "Ham Shank", Class::Other, WeaponSlot::Melee, Melee, &[1013]),
+    weapon("necro_smasher", "Necro Smasher", Class::Other, WeaponSlot::Melee, Melee, &[1123]),
+    weapon("crossing_guard", "Crossing Guard", Class::Other, WeaponSlot::Melee, Melee, &[1127]),
+    // world
+    weapon("world", "World", Class::Other, WeaponSlot::Other, DamageType::Other, &[]),
+    weapon("trigger_hurt", "Environment", Class::Other, WeaponSlot::Other, DamageType::Other, &[]),
+    weapon("player", "Suicide", Class::Other, WeaponSlot::Other, DamageType::Other, &[]),
+    weapon("telefrag", "Telefrag", Class::Other, WeaponSlot::Other, DamageType::Other, &[]),
+    weapon("tf_pumpkin_bomb", "Pumpkin Bomb", Class::Other, WeaponSlot::Other, Blast, &[]),
+];
+
+#[test]
+fn test_weapon_info() {
+    let rocket = WeaponInfo::from_kill_icon("tf_projectile_rocket").unwrap();
+    assert_eq!("Rocket Launcher", rocket.name);
+    assert_eq!(Class::Soldier, rocket.class);
+    assert_eq!(WeaponSlot::Primary, rocket.slot);
+    assert_eq!(DamageType::Blast, rocket.damage_type);
+    assert!(rocket.item_indices.contains(&18));
+
+    let original = WeaponInfo::from_kill_icon("quake_rl").unwrap();
+    assert_eq!(rocket.slot, original.slot);
+
+    let world = WeaponInfo::from_kill_icon("world").unwrap();
+    assert_eq!(WeaponSlot::Other, world.slot);
+    assert_eq!(Class::Other, world.class);
+
+    assert_eq!(
+        Some("knife"),
+        WeaponInfo::from_item_index(194).map(|weapon| weapon.kill_icon)
+    );
+    assert_eq!(None, WeaponInfo::from_kill_icon("not_a_weapon"));
+}
+
+#[test]
+fn test_weapon_kill_icons_unique() {
+    for (index, weapon) in WEAPONS.iter().enumerate() {
+        assert!(
+            WEAPONS[index + 1..]
+                .iter()
+                .all(|other| other.kill_icon != weapon.kill_icon),
+            "duplicate kill icon {}",
+            weapon.kill_icon
+        );
+    }
+}
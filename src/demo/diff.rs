@@ -0,0 +1,477 @@
+//! Finding where two encodings of a demo diverge
+
+use crate::demo::data::DemoTick;
+use crate::demo::header::Header;
+use crate::demo::message::packetentities::{EntityId, PacketEntitiesMessage, PacketEntity};
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::message::MessagePacketMeta;
+use crate::demo::packet::Packet;
+use crate::demo::parser::{DemoHandler, NullHandler, RawPacketStream};
+use crate::demo::sendprop::SendProp;
+use crate::{Demo, Result, Stream};
+use bitbuffer::BitRead;
+use std::fmt::{self, Display, Formatter};
+
+/// The differences between two demos
+#[derive(Debug)]
+pub struct DemoDiff<'a> {
+    /// The headers of both demos, if they differ
+    pub header: Option<Box<[Header; 2]>>,
+    /// The first place where the packets of the demos diverge
+    pub divergence: Option<Divergence<'a>>,
+}
+
+impl DemoDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_none() && self.divergence.is_none()
+    }
+}
+
+impl Display for DemoDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(headers) = &self.header {
+            writeln!(f, "headers differ:")?;
+            writeln!(f, "  left:  {:?}", headers[0])?;
+            writeln!(f, "  right: {:?}", headers[1])?;
+        }
+        match &self.divergence {
+            Some(divergence) => write!(f, "{}", divergence),
+            None if self.header.is_none() => write!(f, "demos are identical"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The first diverging packet of two demos
+#[derive(Debug)]
+pub struct Divergence<'a> {
+    /// Index of the diverging packet
+    pub packet_index: usize,
+    pub tick: DemoTick,
+    /// The bit offsets of the diverging packet, message, entity or prop in both demos
+    pub offsets: [usize; 2],
+    pub kind: DivergenceKind<'a>,
+}
+
+/// What part of the packet diverges, with the decoded values from both demos
+#[derive(Debug)]
+pub enum DivergenceKind<'a> {
+    /// The packets differ in type or content, `None` if the demo ended before the packet
+    Packet([Option<Box<Packet<'a>>>; 2]),
+    /// The tick or meta data of a message packet differ
+    Meta([(DemoTick, MessagePacketMeta); 2]),
+    /// The messages differ, `None` if the packet has no message at the index
+    Message {
+        index: usize,
+        messages: [Option<Box<Message<'a>>>; 2],
+    },
+    /// An entity in a packet entities message differs in anything but its props
+    ///
+    /// If one of the messages is missing the entity, its offset points to the end of its entity updates
+    Entity {
+        message_index: usize,
+        entities: [Option<Box<PacketEntity>>; 2],
+    },
+    /// A prop of an entity differs, `None` if the entity doesn't have the prop
+    ///
+    /// If one of the entities is missing the prop, its offset points to the end of its prop list
+    Prop {
+        message_index: usize,
+        entity: EntityId,
+        props: [Option<SendProp>; 2],
+    },
+    /// All messages are the same, but the padding after the messages differs
+    Padding,
+}
+
+impl Display for Divergence<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "packet {} at tick {} diverges at bit {} (left) and bit {} (right)",
+            self.packet_index,
+            u32::from(self.tick),
+            self.offsets[0],
+            self.offsets[1]
+        )?;
+        match &self.kind {
+            DivergenceKind::Packet([left, right]) => {
+                writeln!(f, "  left:  {:?}", left)?;
+                write!(f, "  right: {:?}", right)
+            }
+            DivergenceKind::Meta([left, right]) => {
+                writeln!(f, "  left:  tick {}, {:?}", u32::from(left.0), left.1)?;
+                write!(f, "  right: tick {}, {:?}", u32::from(right.0), right.1)
+            }
+            DivergenceKind::Message { index, messages } => {
+                writeln!(f, "message {}", index)?;
+                writeln!(f, "  left:  {:?}", messages[0])?;
+                write!(f, "  right: {:?}", messages[1])?;
+                if messages[0] == messages[1] {
+                    write!(
+                        f,
+                        "\nthe decoded messages are equal, only the encoding differs"
+                    )?;
+                }
+                Ok(())
+            }
+            DivergenceKind::Entity {
+                message_index,
+                entities,
+            } => {
+                writeln!(f, "message {} (PacketEntities)", message_index)?;
+                writeln!(f, "  left:  {:?}", entities[0])?;
+                write!(f, "  right: {:?}", entities[1])
+            }
+            DivergenceKind::Prop {
+                message_index,
+                entity,
+                props,
+            } => {
+                writeln!(
+                    f,
+                    "message {} (PacketEntities), entity {}",
+                    message_index, entity
+                )?;
+                writeln!(f, "  left:  {:?}", props[0])?;
+                write!(f, "  right: {:?}", props[1])
+            }
+            DivergenceKind::Padding => write!(f, "the padding after the messages differs"),
+        }
+    }
+}
+
+/// A message with its start and end bit offset
+type MessageWithOffsets<'a> = (Message<'a>, usize, usize);
+
+/// One side of the comparison
+struct Side<'a> {
+    stream: Stream<'a>,
+    packets: RawPacketStream<'a>,
+    handler: DemoHandler<'a, NullHandler>,
+}
+
+impl<'a> Side<'a> {
+    fn new(demo: &Demo<'a>) -> Result<(Self, Header)> {
+        let mut stream = demo.get_stream();
+        let header = Header::read(&mut stream)?;
+        let mut handler = DemoHandler::default();
+        handler.handle_header(&header);
+        Ok((
+            Side {
+                stream: demo.get_stream(),
+                packets: RawPacketStream::new(stream),
+                handler,
+            },
+            header,
+        ))
+    }
+
+    /// Read the next packet with its start and end bit offset
+    fn next(&mut self) -> Result<Option<(Packet<'a>, usize, usize)>> {
+        let start = self.packets.pos();
+        let packet = self.packets.next(&self.handler.state_handler)?;
+        Ok(packet.map(|packet| (packet, start, self.packets.pos())))
+    }
+
+    fn bits(&self, start: usize, end: usize) -> Result<Stream<'a>> {
+        let mut stream = self.stream.clone();
+        stream.set_pos(start)?;
+        Ok(stream.read_bits(end - start)?)
+    }
+
+    /// Get the messages of a message packet with their start and end bit offset,
+    /// and the end bit offset of the message data
+    fn messages(&self, start: usize) -> Result<(Vec<MessageWithOffsets<'a>>, usize)> {
+        let mut stream = self.stream.clone();
+        // packet type, tick and meta
+        stream.set_pos(start + 8 + 32)?;
+        let _meta: MessagePacketMeta = stream.read()?;
+        let length: u32 = stream.read()?;
+        let data_start = stream.pos();
+        let mut data = stream.read_bits(length as usize * 8)?;
+
+        let state = self.handler.get_parser_state();
+        let mut messages = Vec::new();
+        while data.bits_left() > 6 {
+            let message_start = data_start + data.pos();
+            let message_type = MessageType::read(&mut data)?;
+            let message = Message::from_type(message_type, &mut data, state)?;
+            messages.push((message, message_start, data_start + data.pos()));
+        }
+        Ok((messages, data_start + length as usize * 8))
+    }
+
+    /// Get the bit offset of an entity or one of its props in a packet entities message
+    ///
+    /// The entity and prop are given by their position in the message and in the props sorted by index
+    fn entity_offset(
+        &self,
+        message_start: usize,
+        message_end: usize,
+        message: &PacketEntitiesMessage,
+        entity: usize,
+        prop: Option<usize>,
+    ) -> Result<usize> {
+        let mut stream = self.bits(message_start, message_end)?;
+        let _message_type = MessageType::read(&mut stream)?;
+        let (entities, end) = message.bit_offsets(&mut stream, self.handler.get_parser_state())?;
+        let offset = match (entities.get(entity), prop) {
+            (Some(offsets), Some(prop)) => offsets.props.get(prop).copied().unwrap_or(end),
+            (Some(offsets), None) => offsets.entity,
+            (None, _) => end,
+        };
+        Ok(message_start + offset)
+    }
+}
+
+/// Find the first place where two demos diverge
+///
+/// The demos are walked in parallel, the first packet that isn't bit-identical is decoded
+/// to find the diverging message and, for entity updates, the diverging entity and prop.
+pub fn diff_demos<'a>(left: &'a [u8], right: &'a [u8]) -> Result<DemoDiff<'a>> {
+    let left_demo = Demo::new(left);
+    let right_demo = Demo::new(right);
+    let (mut left, left_header) = Side::new(&left_demo)?;
+    let (mut right, right_header) = Side::new(&right_demo)?;
+
+    let header = (left_header != right_header).then(|| Box::new([left_header, right_header]));
+
+    let mut packet_index = 0;
+    let divergence = loop {
+        let left_start = left.packets.pos();
+        let right_start = right.packets.pos();
+        let (left_packet, right_packet) = match (left.next()?, right.next()?) {
+            (None, None) => break None,
+            (left_packet, right_packet) => (left_packet, right_packet),
+        };
+
+        let divergence = match (&left_packet, &right_packet) {
+            (
+                Some((left_packet, left_start, left_end)),
+                Some((right_packet, right_start, right_end)),
+            ) => {
+                if left.bits(*left_start, *left_end)? == right.bits(*right_start, *right_end)? {
+                    None
+                } else {
+                    Some(diff_packets(
+                        &left,
+                        &right,
+                        left_packet,
+                        right_packet,
+                        [*left_start, *right_start],
+                    )?)
+                }
+            }
+            _ => Some((
+                [left_start, right_start],
+                DivergenceKind::Packet([
+                    left_packet
+                        .as_ref()
+                        .map(|(packet, ..)| Box::new(packet.clone())),
+                    right_packet
+                        .as_ref()
+                        .map(|(packet, ..)| Box::new(packet.clone())),
+                ]),
+            )),
+        };
+
+        if let Some((offsets, kind)) = divergence {
+            let tick = left_packet
+                .as_ref()
+                .or(right_packet.as_ref())
+                .map(|(packet, ..)| packet.tick())
+                .unwrap_or_default();
+            break Some(Divergence {
+                packet_index,
+                tick,
+                offsets,
+                kind,
+            });
+        }
+
+        if let Some((packet, ..)) = left_packet {
+            left.handler.handle_packet(packet)?;
+        }
+        if let Some((packet, ..)) = right_packet {
+            right.handler.handle_packet(packet)?;
+        }
+        packet_index += 1;
+    };
+
+    Ok(DemoDiff { header, divergence })
+}
+
+fn diff_packets<'a>(
+    left: &Side<'a>,
+    right: &Side<'a>,
+    left_packet: &Packet<'a>,
+    right_packet: &Packet<'a>,
+    offsets: [usize; 2],
+) -> Result<([usize; 2], DivergenceKind<'a>)> {
+    let (left_message_packet, right_message_packet) = match (left_packet, right_packet) {
+        (Packet::Message(left_packet), Packet::Message(right_packet))
+        | (Packet::Signon(left_packet), Packet::Signon(right_packet)) => {
+            (left_packet, right_packet)
+        }
+        _ => {
+            return Ok((
+                offsets,
+                DivergenceKind::Packet([
+                    Some(Box::new(left_packet.clone())),
+                    Some(Box::new(right_packet.clone())),
+                ]),
+            ))
+        }
+    };
+
+    if left_message_packet.tick != right_message_packet.tick
+        || left_message_packet.meta != right_message_packet.meta
+    {
+        return Ok((
+            offsets,
+            DivergenceKind::Meta([
+                (left_message_packet.tick, left_message_packet.meta.clone()),
+                (right_message_packet.tick, right_message_packet.meta.clone()),
+            ]),
+        ));
+    }
+
+    let (left_messages, left_end) = left.messages(offsets[0])?;
+    let (right_messages, right_end) = right.messages(offsets[1])?;
+    let mut left_messages = left_messages.into_iter();
+    let mut right_messages = right_messages.into_iter();
+    let mut index = 0;
+    loop {
+        match (left_messages.next(), right_messages.next()) {
+            (None, None) => return Ok((offsets, DivergenceKind::Padding)),
+            (
+                Some((left_message, left_start, left_end)),
+                Some((right_message, right_start, right_end)),
+            ) => {
+                if left.bits(left_start, left_end)? != right.bits(right_start, right_end)? {
+                    if let (
+                        Message::PacketEntities(left_entities),
+                        Message::PacketEntities(right_entities),
+                    ) = (&left_message, &right_message)
+                    {
+                        if let Some((kind, entity, prop)) =
+                            diff_entities(index, left_entities, right_entities)
+                        {
+                            let entity_offsets = [
+                                left.entity_offset(
+                                    left_start,
+                                    left_end,
+                                    left_entities,
+                                    entity,
+                                    prop,
+                                )?,
+                                right.entity_offset(
+                                    right_start,
+                                    right_end,
+                                    right_entities,
+                                    entity,
+                                    prop,
+                                )?,
+                            ];
+                            return Ok((entity_offsets, kind));
+                        }
+                    }
+                    return Ok((
+                        [left_start, right_start],
+                        DivergenceKind::Message {
+                            index,
+                            messages: [Some(Box::new(left_message)), Some(Box::new(right_message))],
+                        },
+                    ));
+                }
+            }
+            // one of the packets has fewer messages, the offset for the shorter packet
+            // is the end of its message data
+            (left_message, right_message) => {
+                let message_offsets = [
+                    left_message
+                        .as_ref()
+                        .map(|(_, start, _)| *start)
+                        .unwrap_or(left_end),
+                    right_message
+                        .as_ref()
+                        .map(|(_, start, _)| *start)
+                        .unwrap_or(right_end),
+                ];
+                return Ok((
+                    message_offsets,
+                    DivergenceKind::Message {
+                        index,
+                        messages: [
+                            left_message.map(|(message, ..)| Box::new(message)),
+                            right_message.map(|(message, ..)| Box::new(message)),
+                        ],
+                    },
+                ));
+            }
+        }
+        index += 1;
+    }
+}
+
+/// Find the first diverging entity or prop, with the position of the entity and prop in the message
+///
+/// Returns `None` if the difference isn't in the entities
+fn diff_entities<'a>(
+    message_index: usize,
+    left: &PacketEntitiesMessage,
+    right: &PacketEntitiesMessage,
+) -> Option<(DivergenceKind<'a>, usize, Option<usize>)> {
+    let count = left.entities.len().max(right.entities.len());
+    for index in 0..count {
+        let (left_entity, right_entity) =
+            match (left.entities.get(index), right.entities.get(index)) {
+                (Some(left_entity), Some(right_entity)) => (left_entity, right_entity),
+                (left_entity, right_entity) => {
+                    let kind = DivergenceKind::Entity {
+                        message_index,
+                        entities: [
+                            left_entity.cloned().map(Box::new),
+                            right_entity.cloned().map(Box::new),
+                        ],
+                    };
+                    return Some((kind, index, None));
+                }
+            };
+
+        if left_entity.entity_index != right_entity.entity_index
+            || left_entity.server_class != right_entity.server_class
+            || left_entity.update_type != right_entity.update_type
+            || left_entity.serial_number != right_entity.serial_number
+        {
+            let kind = DivergenceKind::Entity {
+                message_index,
+                entities: [
+                    Some(Box::new(left_entity.clone())),
+                    Some(Box::new(right_entity.clone())),
+                ],
+            };
+            return Some((kind, index, None));
+        }
+
+        let mut left_props: Vec<&SendProp> = left_entity.props.iter().collect();
+        let mut right_props: Vec<&SendProp> = right_entity.props.iter().collect();
+        left_props.sort_by_key(|prop| prop.index);
+        right_props.sort_by_key(|prop| prop.index);
+
+        for prop_index in 0..left_props.len().max(right_props.len()) {
+            let left_prop = left_props.get(prop_index).copied();
+            let right_prop = right_props.get(prop_index).copied();
+            if left_prop != right_prop {
+                let kind = DivergenceKind::Prop {
+                    message_index,
+                    entity: left_entity.entity_index,
+                    props: [left_prop.cloned(), right_prop.cloned()],
+                };
+                return Some((kind, index, Some(prop_index)));
+            }
+        }
+    }
+    None
+}
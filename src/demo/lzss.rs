@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::convert::TryInto;
 
 pub fn decompress(input: &[u8], output: &mut Vec<u8>) {
@@ -44,3 +45,127 @@ fn decompress_(input: &[u8], output: &mut Vec<u8>) -> Option<()> {
         }
     }
 }
+
+const WINDOW_SIZE: usize = 4096;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 16;
+/// Maximum number of earlier positions to check when looking for a match
+const MAX_CHAIN: usize = 256;
+
+/// Compress data in the lzss format used by the source engine
+///
+/// The output starts with the length of the uncompressed data, the same as the input for [`decompress`]
+pub fn compress(input: &[u8], output: &mut Vec<u8>) {
+    output.extend_from_slice(&(input.len() as u32).to_le_bytes());
+
+    // last position for every 3 byte prefix, and the previous position with the same prefix for every position
+    let mut head: HashMap<[u8; MIN_MATCH], usize> = HashMap::new();
+    let mut previous = vec![usize::MAX; input.len()];
+
+    let mut cmd_pos = output.len();
+    let mut cmd_bit = 8;
+
+    let mut pos = 0;
+    while pos <= input.len() {
+        if cmd_bit == 8 {
+            cmd_pos = output.len();
+            output.push(0);
+            cmd_bit = 0;
+        }
+
+        if pos == input.len() {
+            // end marker, a reference with a length of 1
+            output[cmd_pos] |= 1 << cmd_bit;
+            output.extend_from_slice(&[0, 0]);
+            break;
+        }
+
+        let (match_pos, match_len) = find_match(input, pos, &head, &previous);
+        let advance = if match_len >= MIN_MATCH {
+            let offset = pos - match_pos - 1;
+            output[cmd_pos] |= 1 << cmd_bit;
+            output.push((offset >> 4) as u8);
+            output.push((((offset & 0x0F) << 4) | (match_len - 1)) as u8);
+            match_len
+        } else {
+            output.push(input[pos]);
+            1
+        };
+        cmd_bit += 1;
+
+        for (insert_pos, previous) in previous.iter_mut().enumerate().skip(pos).take(advance) {
+            if let Some(prefix) = prefix(input, insert_pos) {
+                if let Some(last) = head.insert(prefix, insert_pos) {
+                    *previous = last;
+                }
+            }
+        }
+        pos += advance;
+    }
+}
+
+fn prefix(input: &[u8], pos: usize) -> Option<[u8; MIN_MATCH]> {
+    input.get(pos..pos + MIN_MATCH)?.try_into().ok()
+}
+
+/// Find the longest earlier match for the data at `pos` within the window
+fn find_match(
+    input: &[u8],
+    pos: usize,
+    head: &HashMap<[u8; MIN_MATCH], usize>,
+    previous: &[usize],
+) -> (usize, usize) {
+    let mut best = (0, 0);
+    let mut candidate = match prefix(input, pos).and_then(|prefix| head.get(&prefix)) {
+        Some(candidate) => *candidate,
+        None => return best,
+    };
+    let max_len = MAX_MATCH.min(input.len() - pos);
+
+    for _ in 0..MAX_CHAIN {
+        if pos - candidate > WINDOW_SIZE {
+            break;
+        }
+        let len = input[candidate..]
+            .iter()
+            .zip(&input[pos..pos + max_len])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.1 {
+            best = (candidate, len);
+            if len == max_len {
+                break;
+            }
+        }
+        match previous[candidate] {
+            usize::MAX => break,
+            next => candidate = next,
+        }
+    }
+    best
+}
+
+#[test]
+fn test_lzss_roundtrip() {
+    fn roundtrip(input: &[u8]) {
+        let mut compressed = Vec::new();
+        compress(input, &mut compressed);
+        let mut decompressed = Vec::new();
+        decompress(&compressed, &mut decompressed);
+        assert_eq!(input, decompressed.as_slice());
+    }
+
+    roundtrip(b"");
+    roundtrip(b"a");
+    roundtrip(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    roundtrip(b"models/player/scout.mdl models/player/soldier.mdl models/player/pyro.mdl");
+    let data: Vec<u8> = (0..20000u32)
+        .map(|i| (i * 7 % 251) as u8 ^ (i / 300) as u8)
+        .collect();
+    roundtrip(&data);
+
+    let repetitive = b"maps/cp_gullywash_final1.bsp".repeat(100);
+    let mut compressed = Vec::new();
+    compress(&repetitive, &mut compressed);
+    assert!(compressed.len() < repetitive.len() / 4);
+}
@@ -0,0 +1,139 @@
+//! Merging multiple demos of the same match into a single demo
+
+use crate::demo::cut::expand_entering_entities;
+use crate::demo::data::DemoTick;
+use crate::demo::header::Header;
+use crate::demo::message::Message;
+use crate::demo::packet::datatable::DataTablePacket;
+use crate::demo::packet::message::MessagePacket;
+use crate::demo::packet::Packet;
+use crate::demo::parser::entitystateanalyser::{EntityState, EntityStateAnalyser};
+use crate::demo::parser::{DemoHandler, RawPacketStream};
+use crate::{Demo, DemoWriter, ParseError, ParserState, Result};
+use bitbuffer::BitRead;
+
+/// Merge multiple demos recorded on the same server into a single demo
+///
+/// The signon data is taken from the first demo, the demos are required to have the same data tables.
+/// Following demos are appended after the last tick of the previous demo, starting with the
+/// full string tables from their own signon. The first entity update of the following demos is
+/// replaced by a full snapshot of their entities, replacing all entities from the previous demo.
+pub fn merge_demos(inputs: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut writer: Option<DemoWriter> = None;
+    let mut data_tables: Option<DataTablePacket> = None;
+    let mut tick_offset = DemoTick::default();
+
+    for (index, input) in inputs.iter().enumerate() {
+        let is_first = index == 0;
+
+        let demo = Demo::new(input);
+        let mut stream = demo.get_stream();
+        let header = Header::read(&mut stream)?;
+        let mut packets = RawPacketStream::new(stream);
+
+        let mut handler = DemoHandler::parse_all_with_analyser(EntityStateAnalyser::new());
+        handler.handle_header(&header);
+        let writer = writer.get_or_insert_with(|| DemoWriter::new(header));
+
+        let mut started = is_first;
+        let mut synced_entities = is_first;
+        let mut last_tick = tick_offset;
+
+        while let Some(packet) = packets.next(&handler.state_handler)? {
+            let out_packet = match &packet {
+                Packet::Stop(_) => break,
+                Packet::DataTables(tables) => {
+                    match &data_tables {
+                        Some(first)
+                            if first.tables != tables.tables
+                                || first.server_classes != tables.server_classes =>
+                        {
+                            return Err(ParseError::IncompatibleDemos(
+                                "demos have different data tables",
+                            ));
+                        }
+                        Some(_) => {}
+                        None => data_tables = Some(tables.clone()),
+                    }
+                    is_first.then(|| packet.clone())
+                }
+                Packet::Signon(_) | Packet::SyncTick(_) => is_first.then(|| packet.clone()),
+                // replaced by the string tables after the signon
+                Packet::StringTables(_) if !is_first => None,
+                _ => {
+                    let state = handler.get_parser_state();
+                    if !started {
+                        started = true;
+                        writer.write_packet(Packet::StringTables(
+                            state.string_table_store.snapshot(tick_offset),
+                        ))?;
+                    }
+
+                    let mut out_packet = packet.clone();
+                    out_packet.set_tick(packet.tick() + tick_offset);
+                    last_tick = out_packet.tick();
+                    if let (false, Packet::Message(message_packet)) = (is_first, &mut out_packet) {
+                        expand_entering_entities(message_packet, state);
+                        remap_string_table_ids(message_packet, state, writer.state());
+                    }
+                    Some(out_packet)
+                }
+            };
+            handler.handle_packet(packet)?;
+
+            if let Some(mut out_packet) = out_packet {
+                if let (false, Packet::Message(message_packet)) = (synced_entities, &mut out_packet)
+                {
+                    synced_entities =
+                        replace_entity_updates(message_packet, handler.borrow_output());
+                }
+                writer.write_packet(out_packet)?;
+            }
+        }
+
+        tick_offset = last_tick + 1;
+    }
+
+    match writer {
+        Some(writer) => writer.finish(),
+        None => Err(ParseError::InvalidDemo("no demos to merge")),
+    }
+}
+
+/// Replace the entity updates in the packet by a full snapshot of the entities after the packet
+///
+/// Returns whether the packet contained any entity updates
+fn replace_entity_updates(packet: &mut MessagePacket, entity_state: &EntityState) -> bool {
+    let mut replaced = false;
+    packet.messages.retain_mut(|message| match message {
+        Message::PacketEntities(_) if replaced => false,
+        Message::PacketEntities(entity_message) => {
+            *entity_message = entity_state.full_update();
+            replaced = true;
+            true
+        }
+        _ => true,
+    });
+    replaced
+}
+
+/// String table updates refer to tables by the order they were created in,
+/// which can differ between the demos
+fn remap_string_table_ids(packet: &mut MessagePacket, source: &ParserState, target: &ParserState) {
+    packet.messages.retain_mut(|message| match message {
+        Message::UpdateStringTable(update) => {
+            let id = source
+                .string_table_store
+                .get_by_id(update.table_id)
+                .and_then(|table| target.string_table_store.id(&table.name));
+            match id {
+                Some(id) => {
+                    update.table_id = id;
+                    true
+                }
+                None => false,
+            }
+        }
+        _ => true,
+    });
+}
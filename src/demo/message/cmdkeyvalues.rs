@@ -0,0 +1,75 @@
+use crate::demo::data::KeyValues;
+use crate::{ReadResult, Stream};
+use bitbuffer::{BitRead, BitWrite, BitWriteStream, LittleEndian};
+use serde::{Deserialize, Serialize};
+
+/// Key values send as a command, used for things like mvm upgrades and `use_action_slot_item`
+///
+/// Key values that can't be decoded are kept as raw data
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(deserialize = "'a: 'static"))]
+pub enum CmdKeyValuesMessage<'a> {
+    KeyValues(KeyValues),
+    Unknown(Stream<'a>),
+}
+
+impl<'a> BitRead<'a, LittleEndian> for CmdKeyValuesMessage<'a> {
+    fn read(stream: &mut Stream<'a>) -> ReadResult<Self> {
+        let length: u32 = stream.read()?;
+        let data = stream.read_bits(length as usize * 8)?;
+        Ok(match data.clone().read() {
+            Ok(key_values) => CmdKeyValuesMessage::KeyValues(key_values),
+            Err(_) => CmdKeyValuesMessage::Unknown(data),
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for CmdKeyValuesMessage<'_> {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        match self {
+            CmdKeyValuesMessage::KeyValues(key_values) => {
+                stream.reserve_byte_length(32, |stream| key_values.write(stream))
+            }
+            CmdKeyValuesMessage::Unknown(data) => {
+                ((data.bit_len() / 8) as u32).write(stream)?;
+                data.write(stream)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cmd_key_values_roundtrip() {
+    use crate::demo::data::{KeyValue, KeyValueData};
+
+    crate::test_roundtrip_write(CmdKeyValuesMessage::KeyValues(KeyValues {
+        entries: vec![KeyValue {
+            key: "use_action_slot_item_server".into(),
+            value: KeyValueData::SubKeys(KeyValues::default()),
+        }],
+    }));
+    crate::test_roundtrip_write(CmdKeyValuesMessage::KeyValues(KeyValues {
+        entries: vec![KeyValue {
+            key: "MvM_UpgradesDone".into(),
+            value: KeyValueData::SubKeys(KeyValues {
+                entries: vec![KeyValue {
+                    key: "num_upgrades".into(),
+                    value: KeyValueData::Int(3),
+                }],
+            }),
+        }],
+    }));
+}
+
+#[test]
+fn test_cmd_key_values_unknown() {
+    use bitbuffer::BitReadBuffer;
+
+    let data = vec![4, 0, 0, 0, 9, b'a', 0, 0];
+    let mut stream = Stream::new(BitReadBuffer::new_owned(data, LittleEndian));
+    let message: CmdKeyValuesMessage = stream.read().unwrap();
+    assert!(matches!(message, CmdKeyValuesMessage::Unknown(_)));
+    crate::test_roundtrip_write(message);
+}
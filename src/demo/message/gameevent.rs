@@ -134,6 +134,13 @@ impl ParseBitSkip<'_> for GameEventMessage {
 )]
 pub struct GameEventTypeId(#[size = 9] u16);
 
+#[cfg(test)]
+impl GameEventTypeId {
+    pub(crate) fn new(id: u16) -> Self {
+        GameEventTypeId(id)
+    }
+}
+
 impl From<GameEventTypeId> for usize {
     fn from(id: GameEventTypeId) -> Self {
         id.0 as usize
@@ -97,6 +97,11 @@ pub struct FixAngleMessage {
     pub z: u16,
 }
 
+/// A message send to a single entity
+///
+/// The layout of the payload is defined by the `ReceiveMessage` implementation of the class
+/// given by `class_id`, which can also be one of the base classes of the entity.
+/// Decoding these payloads is out of scope for the parser and they are always kept as raw data.
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(BitRead, BitWrite, Debug, PartialEq, Serialize, Deserialize, Clone)]
 #[endianness = "LittleEndian"]
@@ -119,30 +124,9 @@ pub struct PreFetchMessage {
     pub index: u16,
 }
 
-#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
-#[derive(BitRead, BitWrite, Debug, PartialEq, Serialize, Deserialize, Clone)]
-#[endianness = "LittleEndian"]
-#[serde(bound(deserialize = "'a: 'static"))]
-pub struct MenuMessage<'a> {
-    pub kind: u16,
-    pub length: u16,
-    #[size = "length.saturating_mul(8)"]
-    pub index: Stream<'a>,
-}
-
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(BitRead, BitWrite, Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct GetCvarValueMessage {
     pub cookie: u32,
     pub value: String,
 }
-
-#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
-#[derive(BitRead, BitWrite, Debug, PartialEq, Serialize, Deserialize, Clone)]
-#[endianness = "LittleEndian"]
-#[serde(bound(deserialize = "'a: 'static"))]
-pub struct CmdKeyValuesMessage<'a> {
-    pub length: u32,
-    #[size = "length.saturating_mul(8)"]
-    pub data: Stream<'a>,
-}
@@ -0,0 +1,119 @@
+use crate::demo::data::KeyValues;
+use crate::{ReadResult, Stream};
+use bitbuffer::{BitRead, BitWrite, BitWriteStream, LittleEndian};
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::convert::TryFrom;
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr, TryFromPrimitive)]
+#[repr(u16)]
+pub enum MenuKind {
+    /// Just a message in the top left corner
+    Message = 0,
+    /// A menu with up to 10 options to choose from
+    Menu = 1,
+    /// A text box
+    Text = 2,
+    /// A text entry box
+    Entry = 3,
+    /// A request to connect to a different server
+    AskConnect = 4,
+}
+
+/// Server plugin menu, the contents of the menu are stored as binary key values
+///
+/// Menus with an unknown kind or key values that can't be decoded are kept as raw data
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+#[serde(bound(deserialize = "'a: 'static"))]
+pub enum MenuMessage<'a> {
+    Menu {
+        kind: MenuKind,
+        key_values: KeyValues,
+    },
+    Unknown {
+        kind: u16,
+        data: Stream<'a>,
+    },
+}
+
+impl<'a> BitRead<'a, LittleEndian> for MenuMessage<'a> {
+    fn read(stream: &mut Stream<'a>) -> ReadResult<Self> {
+        let kind: u16 = stream.read()?;
+        let length: u16 = stream.read()?;
+        let data = stream.read_bits(length as usize * 8)?;
+        let menu = MenuKind::try_from(kind).ok().and_then(|menu_kind| {
+            let mut key_values_data = data.clone();
+            let key_values = key_values_data.read().ok()?;
+            Some(MenuMessage::Menu {
+                kind: menu_kind,
+                key_values,
+            })
+        });
+        Ok(menu.unwrap_or(MenuMessage::Unknown { kind, data }))
+    }
+}
+
+impl BitWrite<LittleEndian> for MenuMessage<'_> {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        match self {
+            MenuMessage::Menu { kind, key_values } => {
+                (*kind as u16).write(stream)?;
+                stream.reserve_byte_length(16, |stream| key_values.write(stream))
+            }
+            MenuMessage::Unknown { kind, data } => {
+                kind.write(stream)?;
+                ((data.bit_len() / 8) as u16).write(stream)?;
+                data.write(stream)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_menu_roundtrip() {
+    use crate::demo::data::{KeyValue, KeyValueData};
+
+    crate::test_roundtrip_write(MenuMessage::Menu {
+        kind: MenuKind::Menu,
+        key_values: KeyValues {
+            entries: vec![KeyValue {
+                key: "menu".into(),
+                value: KeyValueData::SubKeys(KeyValues {
+                    entries: vec![
+                        KeyValue {
+                            key: "title".into(),
+                            value: KeyValueData::String("Pick a map".into()),
+                        },
+                        KeyValue {
+                            key: "level".into(),
+                            value: KeyValueData::Int(1),
+                        },
+                    ],
+                }),
+            }],
+        },
+    });
+}
+
+#[test]
+fn test_menu_unknown() {
+    use bitbuffer::BitReadBuffer;
+
+    // unknown menu kind
+    let data = vec![9, 0, 1, 0, 8];
+    let mut stream = Stream::new(BitReadBuffer::new_owned(data.clone(), LittleEndian));
+    let menu: MenuMessage = stream.read().unwrap();
+    assert!(matches!(menu, MenuMessage::Unknown { kind: 9, .. }));
+    crate::test_roundtrip_write(menu);
+
+    // unknown key value type
+    let data = vec![1, 0, 4, 0, 9, b'a', 0, 0];
+    let mut stream = Stream::new(BitReadBuffer::new_owned(data, LittleEndian));
+    let menu: MenuMessage = stream.read().unwrap();
+    assert!(matches!(menu, MenuMessage::Unknown { kind: 1, .. }));
+    crate::test_roundtrip_write(menu);
+}
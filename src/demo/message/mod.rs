@@ -2,13 +2,16 @@ pub use generated::*;
 
 use crate::demo::message::bspdecal::*;
 use crate::demo::message::classinfo::*;
+use crate::demo::message::cmdkeyvalues::*;
 use crate::demo::message::gameevent::*;
+use crate::demo::message::menu::*;
 use crate::demo::message::packetentities::*;
 use crate::demo::message::setconvar::*;
 use crate::demo::message::stringtable::*;
 use crate::demo::message::tempentities::*;
 use crate::demo::message::usermessage::*;
 use crate::demo::message::voice::*;
+use crate::demo::packet::datatable::ServerClass;
 use crate::demo::parser::{Encode, ParseBitSkip};
 use crate::{Parse, ParserState, Result, Stream};
 use bitbuffer::{BitRead, BitWrite, BitWriteStream, LittleEndian};
@@ -17,8 +20,10 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 
 pub mod bspdecal;
 pub mod classinfo;
+pub mod cmdkeyvalues;
 pub mod gameevent;
 pub mod generated;
+pub mod menu;
 pub mod packetentities;
 pub mod setconvar;
 pub mod stringtable;
@@ -28,7 +33,7 @@ pub mod voice;
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(
-    BitRead, BitWrite, Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr,
+    BitRead, BitWrite, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize_repr, Deserialize_repr,
 )]
 #[repr(u8)]
 #[discriminant_bits = 6]
@@ -270,3 +275,14 @@ impl Encode for Message<'_> {
         }
     }
 }
+
+impl EntityMessage<'_> {
+    pub fn entity_id(&self) -> EntityId {
+        EntityId::from(self.index as u32)
+    }
+
+    /// The server class that the payload is addressed to, see [`EntityMessage`]
+    pub fn server_class<'s>(&self, state: &'s ParserState) -> Option<&'s ServerClass> {
+        state.server_classes.get(usize::from(self.class_id))
+    }
+}
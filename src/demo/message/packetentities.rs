@@ -12,7 +12,7 @@ use parse_display::{Display, FromStr};
 use std::cmp::{min, Ordering};
 use std::collections::HashSet;
 
-use crate::demo::data::ServerTick;
+use crate::demo::data::{ModelType, ServerTick};
 use itertools::Either;
 use std::fmt;
 #[cfg(feature = "trace")]
@@ -72,6 +72,18 @@ impl PartialOrd<u32> for EntityId {
     }
 }
 
+impl BitReadSized<'_, LittleEndian> for EntityId {
+    fn read(stream: &mut Stream, size: usize) -> ReadResult<Self> {
+        Ok(EntityId(stream.read_int(size)?))
+    }
+}
+
+impl BitWriteSized<LittleEndian> for EntityId {
+    fn write_sized(&self, stream: &mut BitWriteStream<LittleEndian>, len: usize) -> ReadResult<()> {
+        self.0.write_sized(stream, len)
+    }
+}
+
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(
     BitRead, BitWrite, Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr,
@@ -142,6 +154,25 @@ impl PacketEntity {
         self.get_prop_by_identifier(&identifier, parser_state)
     }
 
+    /// The index of the entity's model in the `modelprecache` table
+    ///
+    /// Note that updates only contain the model index if it changed
+    pub fn model_index(&self, parser_state: &ParserState) -> Option<u32> {
+        const MODEL_INDEX_PROP: SendPropIdentifier =
+            SendPropIdentifier::new("DT_BaseEntity", "m_nModelIndex");
+        self.get_prop_by_identifier(&MODEL_INDEX_PROP, parser_state)
+            .and_then(|prop| i64::try_from(&prop.value).ok())
+            .map(|index| index as u32)
+    }
+
+    pub fn model_name<'a>(&self, parser_state: &'a ParserState) -> Option<&'a str> {
+        parser_state.model_name(self.model_index(parser_state)?)
+    }
+
+    pub fn model_type(&self, parser_state: &ParserState) -> Option<ModelType> {
+        self.model_name(parser_state).map(ModelType::new)
+    }
+
     pub fn get_baseline_props<'a>(&self, parser_state: &'a ParserState) -> Cow<'a, [SendProp]> {
         parser_state
             .get_baseline(
@@ -236,6 +267,13 @@ fn test_bit_var_roundtrip() {
     }
 }
 
+/// Entity updates for a tick
+///
+/// Encoding an unmodified message gives the bits it was read from. Props are always encoded in
+/// increasing index order, which is the only order the format allows, and floats are quantised
+/// back to the value they were read from. The exceptions are scaled floats with more than 23 bits,
+/// where multiple encoded values read as the same float, and encodings the engine doesn't write,
+/// like a coord with an empty fractional part.
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct PacketEntitiesMessage {
@@ -498,6 +536,7 @@ impl PacketEntitiesMessage {
     ) -> Result<()> {
         let mut last_index: i32 = -1;
 
+        // props are encoded as the difference to the previous index, so they have to be sorted
         let mut props: Vec<&SendProp> = props.into_iter().collect();
         props.sort_by(|a, b| a.index.cmp(&b.index));
 
@@ -524,6 +563,70 @@ impl PacketEntitiesMessage {
     }
 }
 
+/// The bit offsets of an entity and its props in an encoded packet entities message
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EntityBitOffsets {
+    pub entity: usize,
+    /// The offsets of the props, followed by the offset of the end of the prop list
+    pub props: Vec<usize>,
+}
+
+impl PacketEntitiesMessage {
+    /// Find the bit offsets of the entities and props of this message in its encoded form,
+    /// together with the offset of the end of the entity updates
+    ///
+    /// The stream has to start at the message data, the offsets are relative to the start of the stream.
+    pub(crate) fn bit_offsets(
+        &self,
+        stream: &mut Stream,
+        state: &ParserState,
+    ) -> Result<(Vec<EntityBitOffsets>, usize)> {
+        stream.skip_bits(11)?;
+        let _delta: Option<ServerTick> = stream.read()?;
+        stream.skip_bits(1 + 11)?;
+        let length: u32 = stream.read_sized(20)?;
+        let _updated_base_line: bool = stream.read()?;
+
+        let data_start = stream.pos();
+        let mut data = stream.read_bits(length as usize)?;
+
+        let mut offsets = Vec::with_capacity(self.entities.len());
+        for entity in &self.entities {
+            let mut entity_offsets = EntityBitOffsets {
+                entity: data_start + data.pos(),
+                props: Vec::new(),
+            };
+            let _diff: u32 = read_bit_var(&mut data)?;
+            let update_type: UpdateType = data.read()?;
+            if update_type == UpdateType::Enter {
+                Self::read_enter(&mut data, entity.entity_index, state, 0, None)?;
+            }
+            if update_type == UpdateType::Enter || update_type == UpdateType::Preserve {
+                let send_table = get_send_table(state, entity.server_class)?;
+                let mut index: i32 = -1;
+                loop {
+                    entity_offsets.props.push(data_start + data.pos());
+                    if !data.read()? {
+                        break;
+                    }
+                    let diff: u32 = read_bit_var(&mut data)?;
+                    index = index.saturating_add(diff as i32).saturating_add(1);
+                    let definition = send_table.flattened_props.get(index as usize).ok_or(
+                        ParseError::PropIndexOutOfBounds {
+                            index,
+                            prop_count: send_table.flattened_props.len(),
+                            table: send_table.name.to_string(),
+                        },
+                    )?;
+                    SendPropValue::parse(&mut data, &definition.parse_definition)?;
+                }
+            }
+            offsets.push(entity_offsets);
+        }
+        Ok((offsets, data_start + data.pos()))
+    }
+}
+
 impl ParseBitSkip<'_> for PacketEntitiesMessage {
     fn parse_skip(stream: &mut Stream, _state: &ParserState) -> Result<()> {
         stream.skip_bits(11)?;
@@ -3,9 +3,9 @@ use bitbuffer::{
 };
 use num_traits::{PrimInt, Unsigned};
 use serde::{Deserialize, Serialize};
-use snap::raw::{decompress_len, Decoder};
+use snap::raw::{decompress_len, Decoder, Encoder};
 
-use crate::demo::lzss::decompress;
+use crate::demo::lzss::{compress, decompress};
 use crate::demo::packet::stringtable::{
     ExtraData, FixedUserDataSize, StringTable, StringTableEntry,
 };
@@ -15,10 +15,93 @@ use std::borrow::Cow;
 use std::cmp::min;
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound(deserialize = "'a: 'static"))]
 pub struct CreateStringTableMessage<'a> {
     pub table: StringTable<'a>,
+    /// Compression used when encoding the table if `table.compressed` is set
+    ///
+    /// Set to the compression used in the demo when parsing.
+    /// Only created tables can be compressed, string table updates and the string tables packet
+    /// have no compressed form.
+    #[serde(skip)]
+    pub compression: Compression,
+    /// The compressed table data from the demo, if the table was compressed
+    #[serde(skip)]
+    pub compressed_data: Option<CompressedTableData<'a>>,
+}
+
+impl PartialEq for CreateStringTableMessage<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.table.eq(&other.table)
+    }
+}
+
+/// Compression algorithm used for string table data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    Snappy,
+    Lzss,
+}
+
+impl Compression {
+    fn magic(&self) -> &'static [u8; 4] {
+        match self {
+            Compression::Snappy => b"SNAP",
+            Compression::Lzss => b"LZSS",
+        }
+    }
+
+    /// Compress the table data, including the size header and compression type
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let compressed = match self {
+            Compression::Snappy => Encoder::new().compress_vec(data)?,
+            Compression::Lzss => {
+                let mut compressed = Vec::with_capacity(data.len() / 2);
+                compress(data, &mut compressed);
+                compressed
+            }
+        };
+
+        let mut out = Vec::with_capacity(compressed.len() + 12);
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(compressed.len() as u32 + 4).to_le_bytes());
+        out.extend_from_slice(self.magic());
+        out.extend_from_slice(&compressed);
+        Ok(out)
+    }
+}
+
+/// The compressed data of a string table as read from the demo
+///
+/// Since we can't reproduce the exact compression used by the server,
+/// the original data is kept to re-encode unmodified tables identical to the original.
+#[derive(Debug, Clone)]
+pub struct CompressedTableData<'a> {
+    compression: Compression,
+    /// The compressed data, including the size header and compression type
+    raw: Stream<'a>,
+    decompressed: Stream<'a>,
+}
+
+impl<'a> CompressedTableData<'a> {
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Get the original compressed data if the table data didn't change
+    fn get(&self, table_data: &[u8], table_bits: usize) -> Option<Stream<'a>> {
+        if self.decompressed.bit_len() != table_data.len() * 8 {
+            return None;
+        }
+        // the padding of the original data isn't always zeroed
+        let original = self.decompressed.clone().read_bits(table_bits).ok()?;
+        let table_data = BitReadStream::new(BitReadBuffer::new(table_data, LittleEndian))
+            .read_bits(table_bits)
+            .ok()?;
+        (original == table_data).then(|| self.raw.clone())
+    }
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -54,8 +137,10 @@ impl<'a> Parse<'a> for CreateStringTableMessage<'a> {
         let compressed = stream.read()?;
 
         let mut table_data = stream.read_bits(length as usize)?;
+        let mut compressed_data = None;
 
         if compressed {
+            let raw = table_data.clone();
             let decompressed_size: u32 = table_data.read()?;
             let compressed_size: u32 = table_data.read()?;
 
@@ -73,7 +158,7 @@ impl<'a> Parse<'a> for CreateStringTableMessage<'a> {
 
             let magic = table_data.read_string(Some(4))?;
 
-            match magic.as_ref() {
+            let compression = match magic.as_ref() {
                 "SNAP" => {
                     let compressed_data = table_data.read_bytes(compressed_size as usize - 4)?;
 
@@ -95,6 +180,7 @@ impl<'a> Parse<'a> for CreateStringTableMessage<'a> {
 
                     let buffer = BitReadBuffer::new_owned(decompressed_data, LittleEndian);
                     table_data = BitReadStream::new(buffer);
+                    Compression::Snappy
                 }
                 "LZSS" => {
                     let compressed_data = table_data.read_bytes(compressed_size as usize - 4)?;
@@ -110,11 +196,18 @@ impl<'a> Parse<'a> for CreateStringTableMessage<'a> {
 
                     let buffer = BitReadBuffer::new_owned(decompressed_data, LittleEndian);
                     table_data = BitReadStream::new(buffer);
+                    Compression::Lzss
                 }
                 _ => {
                     return Err(ParseError::UnexpectedCompressionType(magic.into_owned()));
                 }
-            }
+            };
+
+            compressed_data = Some(CompressedTableData {
+                compression,
+                raw,
+                decompressed: table_data.clone(),
+            });
         }
 
         let table_meta = StringTableMeta {
@@ -132,7 +225,14 @@ impl<'a> Parse<'a> for CreateStringTableMessage<'a> {
             compressed,
             name,
         };
-        Ok(CreateStringTableMessage { table })
+        Ok(CreateStringTableMessage {
+            table,
+            compression: compressed_data
+                .as_ref()
+                .map(CompressedTableData::compression)
+                .unwrap_or_default(),
+            compressed_data,
+        })
     }
 }
 
@@ -157,35 +257,54 @@ impl<'a> ParseBitSkip<'a> for CreateStringTableMessage<'a> {
 }
 
 impl Encode for CreateStringTableMessage<'_> {
-    fn encode(
-        &self,
-        stream: &mut BitWriteStream<LittleEndian>,
-        _state: &ParserState,
-    ) -> Result<()> {
+    fn encode(&self, stream: &mut BitWriteStream<LittleEndian>, state: &ParserState) -> Result<()> {
         let table = &self.table;
         table.name.write(stream)?;
         table.max_entries.write(stream)?;
         let encode_bits = log_base2(table.max_entries) as usize;
         (table.entries.len() as u16).write_sized(stream, encode_bits + 1)?;
 
-        stream.reserve_int::<ParseError, _>(40, |stream| {
-            table.fixed_user_data_size.is_some().write(stream)?;
-            if let Some(fixed_size) = table.fixed_user_data_size {
-                fixed_size.write(stream)?;
-            }
-
-            // no compression for now
-            false.write(stream)?;
+        let mut table_data = Vec::new();
+        let table_bits = {
+            let mut table_stream = BitWriteStream::new(&mut table_data, LittleEndian);
+            write_string_table_update(&table.entries, &mut table_stream, &table.get_table_meta())?;
+            table_stream.bit_len()
+        };
 
-            let start = stream.bit_len();
+        let original_data = self
+            .compressed_data
+            .as_ref()
+            .filter(|compressed| table.compressed && compressed.compression == self.compression)
+            .and_then(|compressed| compressed.get(&table_data, table_bits));
+        let (compressed, data) = match original_data {
+            Some(data) => (true, data),
+            None if table.compressed => {
+                let compressed_data = self.compression.compress(&table_data)?;
+                (
+                    true,
+                    BitReadStream::new(BitReadBuffer::new_owned(compressed_data, LittleEndian)),
+                )
+            }
+            None => (
+                false,
+                BitReadStream::new(BitReadBuffer::new_owned(table_data, LittleEndian))
+                    .read_bits(table_bits)?,
+            ),
+        };
 
-            let table_meta = table.get_table_meta();
+        if state.protocol_version > 23 {
+            write_var_int(data.bit_len() as u32, stream)?;
+        } else {
+            (data.bit_len() as u32).write_sized(stream, 20)?;
+        }
 
-            write_string_table_update(&table.entries, stream, &table_meta)?;
+        table.fixed_user_data_size.is_some().write(stream)?;
+        if let Some(fixed_size) = table.fixed_user_data_size {
+            fixed_size.write(stream)?;
+        }
 
-            let end = stream.bit_len();
-            Ok(encode_var_int_fixed((end - start) as u32))
-        })?;
+        compressed.write(stream)?;
+        stream.write_bits(&data)?;
 
         Ok(())
     }
@@ -204,6 +323,8 @@ fn test_create_string_table_roundtrip() {
                 client_entries: None,
                 compressed: false,
             },
+            compression: Compression::default(),
+            compressed_data: None,
         },
         &state,
     );
@@ -232,9 +353,68 @@ fn test_create_string_table_roundtrip() {
                 client_entries: None,
                 compressed: false,
             },
+            compression: Compression::default(),
+            compressed_data: None,
+        },
+        &state,
+    );
+    crate::test_roundtrip_encode(
+        CreateStringTableMessage {
+            table: StringTable {
+                name: "table1".into(),
+                entries: (0..64)
+                    .map(|i| {
+                        (
+                            i,
+                            StringTableEntry {
+                                text: Some(format!("models/player/model{}.mdl", i % 9).into()),
+                                extra_data: None,
+                            },
+                        )
+                    })
+                    .collect(),
+                max_entries: 128,
+                fixed_user_data_size: None,
+                client_entries: None,
+                compressed: true,
+            },
+            compression: Compression::default(),
+            compressed_data: None,
         },
         &state,
     );
+
+    for compression in [Compression::Snappy, Compression::Lzss] {
+        let message = CreateStringTableMessage {
+            table: StringTable {
+                name: "table1".into(),
+                entries: vec![(
+                    0,
+                    StringTableEntry {
+                        text: Some("foo".repeat(32).into()),
+                        extra_data: None,
+                    },
+                )],
+                max_entries: 16,
+                fixed_user_data_size: None,
+                client_entries: None,
+                compressed: true,
+            },
+            compression,
+            compressed_data: None,
+        };
+        let mut data = Vec::new();
+        message
+            .encode(&mut BitWriteStream::new(&mut data, LittleEndian), &state)
+            .unwrap();
+        let parsed = CreateStringTableMessage::parse(
+            &mut BitReadStream::new(BitReadBuffer::new_owned(data, LittleEndian)),
+            &state,
+        )
+        .unwrap();
+        assert_eq!(message, parsed);
+        assert_eq!(compression, parsed.compression);
+    }
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::demo::data::MaybeUtf8String;
 use crate::demo::message::packetentities::EntityId;
+use crate::demo::sendprop::{read_bit_vec3_coord, write_bit_vec3_coord};
+use crate::demo::vector::Vector;
 use crate::{ReadResult, Stream};
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -92,6 +94,25 @@ pub enum UserMessage<'a> {
     Rumble(RumbleMessage),
     Fade(FadeMessage),
     HapMeleeContact(HapMeleeContactMessage),
+    VoteStart(VoteStartMessage),
+    VotePass(VotePassMessage),
+    VoteFailed(VoteFailedMessage),
+    VoteSetup(VoteSetupMessage),
+    CallVoteFailed(CallVoteFailedMessage),
+    HudText(HudTextMessage),
+    SayText(SayTextMessage),
+    HudMsg(HudMessage),
+    HudNotify(HudNotifyMessage),
+    HudNotifyCustom(HudNotifyCustomMessage),
+    PlayerStatsUpdate(PlayerStatsUpdateMessage),
+    Damage(DamageMessage),
+    DamageDodged(DamageDodgedMessage),
+    PlayerJarated(PlayerJaratedMessage),
+    PlayerExtinguished(PlayerExtinguishedMessage),
+    PlayerShieldBlocked(PlayerShieldBlockedMessage),
+    BreakModel(BreakModelMessage),
+    PlayerBonusPoints(PlayerBonusPointsMessage),
+    SpawnFlyingBird(SpawnFlyingBirdMessage),
     Unknown(UnknownUserMessage<'a>),
 }
 
@@ -108,6 +129,25 @@ impl UserMessage<'_> {
             UserMessage::Rumble(_) => UserMessageType::Rumble as u8,
             UserMessage::Fade(_) => UserMessageType::Fade as u8,
             UserMessage::HapMeleeContact(_) => UserMessageType::HapMeleeContact as u8,
+            UserMessage::VoteStart(_) => UserMessageType::VoteStart as u8,
+            UserMessage::VotePass(_) => UserMessageType::VotePass as u8,
+            UserMessage::VoteFailed(_) => UserMessageType::VoteFailed as u8,
+            UserMessage::VoteSetup(_) => UserMessageType::VoteSetup as u8,
+            UserMessage::CallVoteFailed(_) => UserMessageType::CallVoteFailed as u8,
+            UserMessage::HudText(_) => UserMessageType::HudText as u8,
+            UserMessage::SayText(_) => UserMessageType::SayText as u8,
+            UserMessage::HudMsg(_) => UserMessageType::HudMsg as u8,
+            UserMessage::HudNotify(_) => UserMessageType::HudNotify as u8,
+            UserMessage::HudNotifyCustom(_) => UserMessageType::HudNotifyCustom as u8,
+            UserMessage::PlayerStatsUpdate(_) => UserMessageType::PlayerStatsUpdate as u8,
+            UserMessage::Damage(_) => UserMessageType::Damage as u8,
+            UserMessage::DamageDodged(_) => UserMessageType::DamageDodged as u8,
+            UserMessage::PlayerJarated(_) => UserMessageType::PlayerJarated as u8,
+            UserMessage::PlayerExtinguished(_) => UserMessageType::PlayerExtinguished as u8,
+            UserMessage::PlayerShieldBlocked(_) => UserMessageType::PlayerShieldBlocked as u8,
+            UserMessage::BreakModel(_) => UserMessageType::BreakModel as u8,
+            UserMessage::PlayerBonusPoints(_) => UserMessageType::PlayerBonusPoints as u8,
+            UserMessage::SpawnFlyingBird(_) => UserMessageType::SpawnFlyingBird as u8,
             UserMessage::Unknown(msg) => msg.raw_type,
         }
     }
@@ -130,6 +170,33 @@ impl<'a> BitRead<'a, LittleEndian> for UserMessage<'a> {
                     UserMessageType::Rumble => UserMessage::Rumble(data.read()?),
                     UserMessageType::Fade => UserMessage::Fade(data.read()?),
                     UserMessageType::HapMeleeContact => UserMessage::HapMeleeContact(data.read()?),
+                    UserMessageType::VoteStart => UserMessage::VoteStart(data.read()?),
+                    UserMessageType::VotePass => UserMessage::VotePass(data.read()?),
+                    UserMessageType::VoteFailed => UserMessage::VoteFailed(data.read()?),
+                    UserMessageType::VoteSetup => UserMessage::VoteSetup(data.read()?),
+                    UserMessageType::CallVoteFailed => UserMessage::CallVoteFailed(data.read()?),
+                    UserMessageType::HudText => UserMessage::HudText(data.read()?),
+                    UserMessageType::SayText => UserMessage::SayText(data.read()?),
+                    UserMessageType::HudMsg => UserMessage::HudMsg(data.read()?),
+                    UserMessageType::HudNotify => UserMessage::HudNotify(data.read()?),
+                    UserMessageType::HudNotifyCustom => UserMessage::HudNotifyCustom(data.read()?),
+                    UserMessageType::PlayerStatsUpdate => {
+                        UserMessage::PlayerStatsUpdate(data.read()?)
+                    }
+                    UserMessageType::Damage => UserMessage::Damage(data.read()?),
+                    UserMessageType::DamageDodged => UserMessage::DamageDodged(data.read()?),
+                    UserMessageType::PlayerJarated => UserMessage::PlayerJarated(data.read()?),
+                    UserMessageType::PlayerExtinguished => {
+                        UserMessage::PlayerExtinguished(data.read()?)
+                    }
+                    UserMessageType::PlayerShieldBlocked => {
+                        UserMessage::PlayerShieldBlocked(data.read()?)
+                    }
+                    UserMessageType::BreakModel => UserMessage::BreakModel(data.read()?),
+                    UserMessageType::PlayerBonusPoints => {
+                        UserMessage::PlayerBonusPoints(data.read()?)
+                    }
+                    UserMessageType::SpawnFlyingBird => UserMessage::SpawnFlyingBird(data.read()?),
                     _ => UserMessage::Unknown(UnknownUserMessage {
                         raw_type: message_type as u8,
                         data,
@@ -171,6 +238,25 @@ impl<'a> BitWrite<LittleEndian> for UserMessage<'a> {
             UserMessage::Rumble(body) => stream.write(body),
             UserMessage::Fade(body) => stream.write(body),
             UserMessage::HapMeleeContact(body) => stream.write(body),
+            UserMessage::VoteStart(body) => stream.write(body),
+            UserMessage::VotePass(body) => stream.write(body),
+            UserMessage::VoteFailed(body) => stream.write(body),
+            UserMessage::VoteSetup(body) => stream.write(body),
+            UserMessage::CallVoteFailed(body) => stream.write(body),
+            UserMessage::HudText(body) => stream.write(body),
+            UserMessage::SayText(body) => stream.write(body),
+            UserMessage::HudMsg(body) => stream.write(body),
+            UserMessage::HudNotify(body) => stream.write(body),
+            UserMessage::HudNotifyCustom(body) => stream.write(body),
+            UserMessage::PlayerStatsUpdate(body) => stream.write(body),
+            UserMessage::Damage(body) => stream.write(body),
+            UserMessage::DamageDodged(body) => stream.write(body),
+            UserMessage::PlayerJarated(body) => stream.write(body),
+            UserMessage::PlayerExtinguished(body) => stream.write(body),
+            UserMessage::PlayerShieldBlocked(body) => stream.write(body),
+            UserMessage::BreakModel(body) => stream.write(body),
+            UserMessage::PlayerBonusPoints(body) => stream.write(body),
+            UserMessage::SpawnFlyingBird(body) => stream.write(body),
             UserMessage::Unknown(body) => stream.write(&body.data),
         })?;
 
@@ -444,6 +530,771 @@ pub struct HapMeleeContactMessage {
     pub data: u8,
 }
 
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HudTextMessage {
+    pub text: MaybeUtf8String,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[endianness = "LittleEndian"]
+pub struct SayTextMessage {
+    #[size = 8]
+    pub client: EntityId,
+    pub text: MaybeUtf8String,
+    pub chat: u8,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HudMessage {
+    pub channel: u8,
+    pub x: f32,
+    pub y: f32,
+    pub color: [u8; 4],
+    pub highlight_color: [u8; 4],
+    pub effect: u8,
+    pub fade_in: f32,
+    pub fade_out: f32,
+    pub hold_time: f32,
+    pub fx_time: f32,
+    pub text: MaybeUtf8String,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HudNotifyMessage {
+    pub notification: u8,
+    pub force_show: bool,
+}
+
+impl BitRead<'_, LittleEndian> for HudNotifyMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let notification = stream.read()?;
+        // older demos don't have the `force_show` flag
+        let force_show = stream.bits_left() > 0 && stream.read()?;
+        Ok(HudNotifyMessage {
+            notification,
+            force_show,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for HudNotifyMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.notification.write(stream)?;
+        self.force_show.write(stream)
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HudNotifyCustomMessage {
+    pub text: MaybeUtf8String,
+    pub icon: MaybeUtf8String,
+    pub team: u8,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStatsUpdateMessage {
+    pub class: u8,
+    pub message_type: u8,
+    /// Value of every stat included in the update, indexed by the stat
+    pub stats: [Option<i32>; 32],
+}
+
+impl BitRead<'_, LittleEndian> for PlayerStatsUpdateMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let class = stream.read()?;
+        let message_type = stream.read()?;
+        let mask: u32 = stream.read()?;
+        let mut stats = [None; 32];
+        for (stat, value) in stats.iter_mut().enumerate() {
+            if mask & (1 << stat) != 0 {
+                *value = Some(stream.read()?);
+            }
+        }
+        Ok(PlayerStatsUpdateMessage {
+            class,
+            message_type,
+            stats,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for PlayerStatsUpdateMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.class.write(stream)?;
+        self.message_type.write(stream)?;
+        let mask = self
+            .stats
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_some())
+            .fold(0u32, |mask, (stat, _)| mask | (1 << stat));
+        mask.write(stream)?;
+        for value in self.stats.iter().flatten() {
+            value.write(stream)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageMessage {
+    pub amount: u16,
+    pub damage_type: u32,
+    /// Source of the damage if it should be shown in the damage indicator
+    pub origin: Option<Vector>,
+}
+
+impl BitRead<'_, LittleEndian> for DamageMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let amount = stream.read()?;
+        let damage_type = stream.read()?;
+        let origin = if stream.read()? {
+            Some(read_bit_vec3_coord(stream)?)
+        } else {
+            None
+        };
+        Ok(DamageMessage {
+            amount,
+            damage_type,
+            origin,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for DamageMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.amount.write(stream)?;
+        self.damage_type.write(stream)?;
+        self.origin.is_some().write(stream)?;
+        if let Some(origin) = self.origin {
+            write_bit_vec3_coord(origin, stream)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageDodgedMessage {
+    pub amount: u16,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[endianness = "LittleEndian"]
+pub struct PlayerJaratedMessage {
+    #[size = 8]
+    pub attacker: EntityId,
+    #[size = 8]
+    pub victim: EntityId,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[endianness = "LittleEndian"]
+pub struct PlayerExtinguishedMessage {
+    #[size = 8]
+    pub healer: EntityId,
+    #[size = 8]
+    pub victim: EntityId,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[endianness = "LittleEndian"]
+pub struct PlayerShieldBlockedMessage {
+    #[size = 8]
+    pub attacker: EntityId,
+    #[size = 8]
+    pub blocker: EntityId,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakModelMessage {
+    pub model_index: u16,
+    pub origin: Vector,
+    pub angles: Vector,
+    pub skin: Option<u16>,
+}
+
+impl BitRead<'_, LittleEndian> for BreakModelMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let model_index = stream.read()?;
+        let origin = read_bit_vec3_coord(stream)?;
+        let angles = read_bit_vec3_coord(stream)?;
+        // older demos don't include the skin
+        let skin = if stream.bits_left() >= 16 {
+            Some(stream.read()?)
+        } else {
+            None
+        };
+        Ok(BreakModelMessage {
+            model_index,
+            origin,
+            angles,
+            skin,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for BreakModelMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.model_index.write(stream)?;
+        write_bit_vec3_coord(self.origin, stream)?;
+        write_bit_vec3_coord(self.angles, stream)?;
+        if let Some(skin) = self.skin {
+            skin.write(stream)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[endianness = "LittleEndian"]
+pub struct PlayerBonusPointsMessage {
+    pub points: u8,
+    #[size = 8]
+    pub player: EntityId,
+    #[size = 16]
+    pub source: EntityId,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnFlyingBirdMessage {
+    pub origin: Vector,
+}
+
+impl BitRead<'_, LittleEndian> for SpawnFlyingBirdMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        Ok(SpawnFlyingBirdMessage {
+            origin: read_bit_vec3_coord(stream)?,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for SpawnFlyingBirdMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        write_bit_vec3_coord(self.origin, stream)
+    }
+}
+
+#[test]
+fn test_tf_message_roundtrip() {
+    crate::test_roundtrip_write(UserMessage::HudText(HudTextMessage {
+        text: "#TF_Arena_ProTip".into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::SayText(SayTextMessage {
+        client: 0u32.into(),
+        text: "Server will restart".into(),
+        chat: 1,
+    }));
+    crate::test_roundtrip_write(UserMessage::HudMsg(HudMessage {
+        channel: 1,
+        x: -1.0,
+        y: 0.25,
+        color: [255, 255, 255, 255],
+        highlight_color: [255, 0, 0, 255],
+        effect: 0,
+        fade_in: 0.1,
+        fade_out: 0.2,
+        hold_time: 5.0,
+        fx_time: 0.0,
+        text: "Overtime".into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::HudNotify(HudNotifyMessage {
+        notification: 3,
+        force_show: true,
+    }));
+    crate::test_roundtrip_write(UserMessage::HudNotifyCustom(HudNotifyCustomMessage {
+        text: "Setup ends in 10 seconds".into(),
+        icon: "ico_notify_ten_seconds".into(),
+        team: 0,
+    }));
+    crate::test_roundtrip_write(UserMessage::PlayerStatsUpdate(PlayerStatsUpdateMessage {
+        class: 7,
+        message_type: 0,
+        stats: {
+            let mut stats = [None; 32];
+            stats[3] = Some(12);
+            stats[4] = Some(2);
+            stats[22] = Some(1231);
+            stats
+        },
+    }));
+    crate::test_roundtrip_write(UserMessage::Damage(DamageMessage {
+        amount: 64,
+        damage_type: 2359360,
+        origin: Some(Vector {
+            x: 120.5,
+            y: -512.0,
+            z: 0.0,
+        }),
+    }));
+    crate::test_roundtrip_write(UserMessage::Damage(DamageMessage {
+        amount: 5,
+        damage_type: 8,
+        origin: None,
+    }));
+    crate::test_roundtrip_write(UserMessage::DamageDodged(DamageDodgedMessage {
+        amount: 90,
+    }));
+    crate::test_roundtrip_write(UserMessage::PlayerJarated(PlayerJaratedMessage {
+        attacker: 3u32.into(),
+        victim: 12u32.into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::PlayerExtinguished(PlayerExtinguishedMessage {
+        healer: 4u32.into(),
+        victim: 8u32.into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::PlayerShieldBlocked(
+        PlayerShieldBlockedMessage {
+            attacker: 4u32.into(),
+            blocker: 9u32.into(),
+        },
+    ));
+    crate::test_roundtrip_write(UserMessage::BreakModel(BreakModelMessage {
+        model_index: 312,
+        origin: Vector {
+            x: 1024.0,
+            y: -32.5,
+            z: 64.0,
+        },
+        angles: Vector {
+            x: 0.0,
+            y: 90.0,
+            z: 0.0,
+        },
+        skin: Some(1),
+    }));
+    crate::test_roundtrip_write(UserMessage::PlayerBonusPoints(PlayerBonusPointsMessage {
+        points: 1,
+        player: 5u32.into(),
+        source: 412u32.into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::SpawnFlyingBird(SpawnFlyingBirdMessage {
+        origin: Vector {
+            x: -256.0,
+            y: 128.25,
+            z: 32.0,
+        },
+    }));
+}
+
+/// Older demos don't include the vote index in the vote messages, we detect the format by checking
+/// which of the layouts reads exactly the data of the message with an issue display string
+/// that starts with a `#`, preferring the newer layout
+fn read_vote_message<'a, T>(
+    stream: &mut Stream<'a>,
+    read: impl Fn(&mut Stream<'a>, bool) -> ReadResult<T>,
+    issue: impl Fn(&T) -> &MaybeUtf8String,
+) -> ReadResult<T> {
+    for has_vote_index in [true, false] {
+        let mut attempt = stream.clone();
+        if let Ok(message) = read(&mut attempt, has_vote_index) {
+            if attempt.bits_left() == 0 && issue(&message).as_ref().starts_with('#') {
+                *stream = attempt;
+                return Ok(message);
+            }
+        }
+    }
+    read(stream, true)
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoteStartMessage {
+    /// Team that can vote, 255 if everyone can vote
+    pub team: u8,
+    pub vote_index: Option<u32>,
+    pub initiator: EntityId,
+    pub issue: MaybeUtf8String,
+    pub details: MaybeUtf8String,
+    pub yes_no: bool,
+    /// Entity targeted by the vote, 0 if the vote has no target
+    pub target: Option<EntityId>,
+}
+
+impl BitRead<'_, LittleEndian> for VoteStartMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        read_vote_message(
+            stream,
+            |stream, has_vote_index| {
+                let team = stream.read()?;
+                let vote_index = if has_vote_index {
+                    Some(stream.read()?)
+                } else {
+                    None
+                };
+                let initiator = EntityId::from(stream.read::<u8>()? as u32);
+                let issue = stream.read()?;
+                let details = stream.read()?;
+                let yes_no = stream.read()?;
+                let target = if stream.bits_left() >= 8 {
+                    Some(EntityId::from(stream.read::<u8>()? as u32))
+                } else {
+                    None
+                };
+
+                Ok(VoteStartMessage {
+                    team,
+                    vote_index,
+                    initiator,
+                    issue,
+                    details,
+                    yes_no,
+                    target,
+                })
+            },
+            |message| &message.issue,
+        )
+    }
+}
+
+impl BitWrite<LittleEndian> for VoteStartMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.team.write(stream)?;
+        if let Some(vote_index) = self.vote_index {
+            vote_index.write(stream)?;
+        }
+        (u32::from(self.initiator) as u8).write(stream)?;
+        self.issue.write(stream)?;
+        self.details.write(stream)?;
+        self.yes_no.write(stream)?;
+        if let Some(target) = self.target {
+            (u32::from(target) as u8).write(stream)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VotePassMessage {
+    pub team: u8,
+    pub vote_index: Option<u32>,
+    pub issue: MaybeUtf8String,
+    pub details: MaybeUtf8String,
+}
+
+impl BitRead<'_, LittleEndian> for VotePassMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        read_vote_message(
+            stream,
+            |stream, has_vote_index| {
+                let team = stream.read()?;
+                let vote_index = if has_vote_index {
+                    Some(stream.read()?)
+                } else {
+                    None
+                };
+
+                Ok(VotePassMessage {
+                    team,
+                    vote_index,
+                    issue: stream.read()?,
+                    details: stream.read()?,
+                })
+            },
+            |message| &message.issue,
+        )
+    }
+}
+
+impl BitWrite<LittleEndian> for VotePassMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.team.write(stream)?;
+        if let Some(vote_index) = self.vote_index {
+            vote_index.write(stream)?;
+        }
+        self.issue.write(stream)?;
+        self.details.write(stream)
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoteFailedMessage {
+    pub team: u8,
+    pub vote_index: Option<u32>,
+    pub reason: u8,
+}
+
+impl BitRead<'_, LittleEndian> for VoteFailedMessage {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let team = stream.read()?;
+        // old format is only 2 bytes, new format includes the 4 byte vote index
+        let vote_index = if stream.bits_left() >= 40 {
+            Some(stream.read()?)
+        } else {
+            None
+        };
+
+        Ok(VoteFailedMessage {
+            team,
+            vote_index,
+            reason: stream.read()?,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for VoteFailedMessage {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.team.write(stream)?;
+        if let Some(vote_index) = self.vote_index {
+            vote_index.write(stream)?;
+        }
+        self.reason.write(stream)
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteFailReason {
+    Generic,
+    TransitioningPlayers,
+    RateExceeded,
+    YesMustExceedNo,
+    QuorumFailure,
+    IssueDisabled,
+    MapNotFound,
+    MapNameRequired,
+    FailedRecently,
+    TeamCantCall,
+    WaitingForPlayers,
+    PlayerNotFound,
+    CannotKickAdmin,
+    ScrambleInProgress,
+    Spectator,
+    NextLevelSet,
+    MapNotValid,
+    CannotKickForTime,
+    CannotKickDuringRound,
+    VoteInProgress,
+    KickLimitReached,
+    KickDeniedByGcBan,
+    /// Reason not known to the parser
+    Unknown(u8),
+}
+
+impl VoteFailReason {
+    pub fn new(reason: u8) -> Self {
+        match reason {
+            0 => VoteFailReason::Generic,
+            1 => VoteFailReason::TransitioningPlayers,
+            2 => VoteFailReason::RateExceeded,
+            3 => VoteFailReason::YesMustExceedNo,
+            4 => VoteFailReason::QuorumFailure,
+            5 => VoteFailReason::IssueDisabled,
+            6 => VoteFailReason::MapNotFound,
+            7 => VoteFailReason::MapNameRequired,
+            8 => VoteFailReason::FailedRecently,
+            9 => VoteFailReason::TeamCantCall,
+            10 => VoteFailReason::WaitingForPlayers,
+            11 => VoteFailReason::PlayerNotFound,
+            12 => VoteFailReason::CannotKickAdmin,
+            13 => VoteFailReason::ScrambleInProgress,
+            14 => VoteFailReason::Spectator,
+            15 => VoteFailReason::NextLevelSet,
+            16 => VoteFailReason::MapNotValid,
+            17 => VoteFailReason::CannotKickForTime,
+            18 => VoteFailReason::CannotKickDuringRound,
+            19 => VoteFailReason::VoteInProgress,
+            20 => VoteFailReason::KickLimitReached,
+            21 => VoteFailReason::KickDeniedByGcBan,
+            reason => VoteFailReason::Unknown(reason),
+        }
+    }
+}
+
+impl From<VoteFailReason> for u8 {
+    fn from(reason: VoteFailReason) -> Self {
+        match reason {
+            VoteFailReason::Generic => 0,
+            VoteFailReason::TransitioningPlayers => 1,
+            VoteFailReason::RateExceeded => 2,
+            VoteFailReason::YesMustExceedNo => 3,
+            VoteFailReason::QuorumFailure => 4,
+            VoteFailReason::IssueDisabled => 5,
+            VoteFailReason::MapNotFound => 6,
+            VoteFailReason::MapNameRequired => 7,
+            VoteFailReason::FailedRecently => 8,
+            VoteFailReason::TeamCantCall => 9,
+            VoteFailReason::WaitingForPlayers => 10,
+            VoteFailReason::PlayerNotFound => 11,
+            VoteFailReason::CannotKickAdmin => 12,
+            VoteFailReason::ScrambleInProgress => 13,
+            VoteFailReason::Spectator => 14,
+            VoteFailReason::NextLevelSet => 15,
+            VoteFailReason::MapNotValid => 16,
+            VoteFailReason::CannotKickForTime => 17,
+            VoteFailReason::CannotKickDuringRound => 18,
+            VoteFailReason::VoteInProgress => 19,
+            VoteFailReason::KickLimitReached => 20,
+            VoteFailReason::KickDeniedByGcBan => 21,
+            VoteFailReason::Unknown(reason) => reason,
+        }
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoteSetupMessage {
+    pub count: u8,
+    #[size = "count"]
+    pub issues: Vec<VoteIssue>,
+}
+
+impl<E: Endianness> BitWrite<E> for VoteSetupMessage {
+    fn write(&self, stream: &mut BitWriteStream<E>) -> ReadResult<()> {
+        self.count.write(stream)?;
+        for issue in &self.issues {
+            issue.write(stream)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoteIssue {
+    pub name: MaybeUtf8String,
+    pub translation: MaybeUtf8String,
+    pub active: u8,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(BitRead, BitWrite, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallVoteFailedMessage {
+    pub reason: u8,
+    /// Time in seconds before a new vote can be called
+    pub time: u16,
+}
+
+#[test]
+fn test_vote_message_roundtrip() {
+    crate::test_roundtrip_write(UserMessage::VoteStart(VoteStartMessage {
+        team: 3,
+        vote_index: Some(2),
+        initiator: 4u32.into(),
+        issue: "#TF_vote_kick_player_other".into(),
+        details: "Player".into(),
+        yes_no: true,
+        target: Some(5u32.into()),
+    }));
+    crate::test_roundtrip_write(UserMessage::VoteStart(VoteStartMessage {
+        team: 255,
+        vote_index: None,
+        initiator: 4u32.into(),
+        issue: "#TF_vote_changelevel".into(),
+        details: "cp_process_final".into(),
+        yes_no: true,
+        target: None,
+    }));
+    crate::test_roundtrip_write(UserMessage::VotePass(VotePassMessage {
+        team: 3,
+        vote_index: Some(2),
+        issue: "#TF_vote_passed_kick_player".into(),
+        details: "Player".into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::VotePass(VotePassMessage {
+        team: 3,
+        vote_index: None,
+        issue: "#TF_vote_passed_kick_player".into(),
+        details: "Player".into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::VoteFailed(VoteFailedMessage {
+        team: 2,
+        vote_index: Some(3),
+        reason: VoteFailReason::YesMustExceedNo.into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::VoteFailed(VoteFailedMessage {
+        team: 2,
+        vote_index: None,
+        reason: VoteFailReason::QuorumFailure.into(),
+    }));
+    crate::test_roundtrip_write(UserMessage::VoteSetup(VoteSetupMessage {
+        count: 2,
+        issues: vec![
+            VoteIssue {
+                name: "Kick".into(),
+                translation: "#TF_Kick".into(),
+                active: 1,
+            },
+            VoteIssue {
+                name: "ChangeLevel".into(),
+                translation: "#TF_ChangeLevel".into(),
+                active: 0,
+            },
+        ],
+    }));
+    crate::test_roundtrip_write(UserMessage::CallVoteFailed(CallVoteFailedMessage {
+        reason: VoteFailReason::FailedRecently.into(),
+        time: 120,
+    }));
+}
+
+#[test]
+fn test_vote_message_read() {
+    use bitbuffer::BitReadBuffer;
+
+    fn read_exact<T: for<'a> BitRead<'a, LittleEndian>>(
+        write: impl Fn(&mut BitWriteStream<LittleEndian>) -> ReadResult<()>,
+    ) -> T {
+        let mut data = Vec::new();
+        let bit_len = {
+            let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+            write(&mut stream).unwrap();
+            stream.bit_len()
+        };
+        let mut stream = Stream::new(BitReadBuffer::new_owned(data, LittleEndian));
+        stream.read_bits(bit_len).unwrap().read().unwrap()
+    }
+
+    // vote index 35 starts with 0x23, the same byte as the `#` of the issue
+    let start = VoteStartMessage {
+        team: 3,
+        vote_index: Some(35),
+        initiator: 4u32.into(),
+        issue: "#TF_vote_kick_player_other".into(),
+        details: "Player".into(),
+        yes_no: true,
+        target: Some(5u32.into()),
+    };
+    assert_eq!(
+        start,
+        read_exact::<VoteStartMessage>(|stream| start.write(stream))
+    );
+    let pass = VotePassMessage {
+        team: 3,
+        vote_index: Some(35),
+        issue: "#TF_vote_passed_kick_player".into(),
+        details: "Player".into(),
+    };
+    assert_eq!(
+        pass,
+        read_exact::<VotePassMessage>(|stream| pass.write(stream))
+    );
+
+    let old_pass = VotePassMessage {
+        vote_index: None,
+        ..pass
+    };
+    assert_eq!(
+        old_pass,
+        read_exact::<VotePassMessage>(|stream| old_pass.write(stream))
+    );
+}
+
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(bound(deserialize = "'a: 'static"))]
@@ -1,14 +1,21 @@
-use bitbuffer::{BitRead, BitWrite, BitWriteSized, BitWriteStream, LittleEndian};
+use bitbuffer::{BitRead, BitReadBuffer, BitWrite, BitWriteSized, BitWriteStream, LittleEndian};
+use enumflags2::{bitflags, BitFlags};
+use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
-use crate::{ReadResult, Stream};
+use crate::demo::message::packetentities::EntityId;
+#[cfg(test)]
+use crate::demo::packet::stringtable::{StringTable, StringTableEntry};
+use crate::demo::vector::Vector;
+use crate::{ParserState, ReadResult, Result, Stream};
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VoiceInitMessage {
-    codec: String,
-    quality: u8,
-    sampling_rate: u16,
+    pub codec: String,
+    pub quality: u8,
+    pub sampling_rate: u16,
 }
 
 impl BitRead<'_, LittleEndian> for VoiceInitMessage {
@@ -67,11 +74,12 @@ fn test_voice_init_roundtrip() {
 #[endianness = "LittleEndian"]
 #[serde(bound(deserialize = "'a: 'static"))]
 pub struct VoiceDataMessage<'a> {
-    client: u8,
-    proximity: u8,
-    length: u16,
+    /// Client index of the speaker, one lower than the entity id
+    pub client: u8,
+    pub proximity: u8,
+    pub length: u16,
     #[size = "length"]
-    data: Stream<'a>,
+    pub data: Stream<'a>,
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -123,6 +131,401 @@ impl<'a> BitWrite<LittleEndian> for ParseSoundsMessage<'a> {
     }
 }
 
+impl ParseSoundsMessage<'_> {
+    /// Decode the sounds in the message, sound names are resolved from the `soundprecache` table
+    pub fn sounds(&self, state: &ParserState) -> Result<Vec<SoundInfo>> {
+        let mut data = self.data.clone();
+        let mut sounds = Vec::with_capacity(self.num as usize);
+        let mut delta = SoundInfo::default();
+        for _ in 0..self.num {
+            let mut sound = SoundInfo::read_delta(&mut data, &delta, state.protocol_version)?;
+            sound.name = state.sound_name(sound.sound_index).map(String::from);
+            sounds.push(sound.clone());
+            delta = sound;
+        }
+        Ok(sounds)
+    }
+
+    pub fn from_sounds(
+        sounds: &[SoundInfo],
+        reliable: bool,
+        protocol_version: u32,
+    ) -> ReadResult<ParseSoundsMessage<'static>> {
+        let mut data = Vec::new();
+        let length = {
+            let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+            let mut delta = &SoundInfo::default();
+            for sound in sounds {
+                sound.write_delta(&mut stream, delta, protocol_version)?;
+                delta = sound;
+            }
+            stream.bit_len()
+        };
+        let buffer = BitReadBuffer::new_owned(data, LittleEndian);
+        Ok(ParseSoundsMessage {
+            reliable,
+            num: sounds.len() as u8,
+            length: length as u16,
+            data: Stream::new(buffer).read_bits(length)?,
+        })
+    }
+}
+
+const SND_STOP: u16 = 4;
+const SOUND_DELAY_OFFSET: f32 = 0.1;
+const MAX_EDICT_BITS: usize = 11;
+const SND_FLAG_BITS_ENCODE: usize = 11;
+const SOUND_SEQUENCE_BITS: usize = 10;
+const MAX_SNDLVL_BITS: usize = 9;
+const SOUND_DELAY_BITS: usize = 13;
+const COORD_BITS: usize = 12;
+
+#[bitflags]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[repr(u16)]
+pub enum SoundFlag {
+    ChangeVolume = 1,
+    ChangePitch = 2,
+    Stop = 4,
+    Spawning = 8,
+    Delay = 16,
+    StopLooping = 32,
+    Speaker = 64,
+    ShouldPause = 128,
+    IgnorePhonemes = 256,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TryFromPrimitive)]
+#[repr(u8)]
+pub enum SoundChannel {
+    Auto = 0,
+    Weapon = 1,
+    Voice = 2,
+    Item = 3,
+    Body = 4,
+    Stream = 5,
+    Static = 6,
+    VoiceBase = 7,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundInfo {
+    pub entity: EntityId,
+    pub sound_index: u16,
+    /// Name of the sound from the `soundprecache` table
+    pub name: Option<String>,
+    pub flags: u16,
+    pub channel: SoundChannel,
+    pub is_ambient: bool,
+    pub is_sentence: bool,
+    pub sequence: u16,
+    pub volume: f32,
+    pub sound_level: u16,
+    pub pitch: u8,
+    pub special_dsp: u8,
+    /// Delay in seconds, negative values mean the sound should be skipped ahead
+    pub delay: f32,
+    pub origin: Vector,
+    pub speaker_entity: i32,
+}
+
+impl Default for SoundInfo {
+    fn default() -> Self {
+        SoundInfo {
+            entity: EntityId::default(),
+            sound_index: 0,
+            name: None,
+            flags: 0,
+            channel: SoundChannel::Static,
+            is_ambient: false,
+            is_sentence: false,
+            sequence: 0,
+            volume: 1.0,
+            sound_level: 75,
+            pitch: 100,
+            special_dsp: 0,
+            delay: 0.0,
+            origin: Vector::default(),
+            speaker_entity: -1,
+        }
+    }
+}
+
+fn read_delta<T: Copy>(
+    stream: &mut Stream,
+    delta: T,
+    read: impl FnOnce(&mut Stream) -> ReadResult<T>,
+) -> ReadResult<T> {
+    if stream.read()? {
+        read(stream)
+    } else {
+        Ok(delta)
+    }
+}
+
+fn write_delta<T: PartialEq>(
+    stream: &mut BitWriteStream<LittleEndian>,
+    value: T,
+    delta: T,
+    write: impl FnOnce(&mut BitWriteStream<LittleEndian>, T) -> ReadResult<()>,
+) -> ReadResult<()> {
+    let changed = value != delta;
+    changed.write(stream)?;
+    if changed {
+        write(stream, value)?;
+    }
+    Ok(())
+}
+
+impl SoundInfo {
+    pub fn flags(&self) -> BitFlags<SoundFlag> {
+        BitFlags::from_bits_truncate(self.flags)
+    }
+
+    pub fn is_stop(&self) -> bool {
+        self.flags == SND_STOP
+    }
+
+    fn sound_index_bits(protocol_version: u32) -> usize {
+        if protocol_version > 22 {
+            14
+        } else {
+            13
+        }
+    }
+
+    fn read_delta(
+        stream: &mut Stream,
+        delta: &SoundInfo,
+        protocol_version: u32,
+    ) -> ReadResult<Self> {
+        let entity = read_delta(stream, delta.entity, |stream| {
+            let bits = if stream.read()? { 5 } else { MAX_EDICT_BITS };
+            Ok(EntityId::from(stream.read_int::<u32>(bits)?))
+        })?;
+        let sound_index = read_delta(stream, delta.sound_index, |stream| {
+            stream.read_int(Self::sound_index_bits(protocol_version))
+        })?;
+        let flags = read_delta(stream, delta.flags, |stream| {
+            stream.read_int(SND_FLAG_BITS_ENCODE)
+        })?;
+        let channel = read_delta(stream, delta.channel, |stream| {
+            Ok(SoundChannel::try_from(stream.read_int::<u8>(3)?).unwrap_or(SoundChannel::Auto))
+        })?;
+        let is_ambient = stream.read()?;
+        let is_sentence = stream.read()?;
+
+        let mut sound = SoundInfo {
+            entity,
+            sound_index,
+            flags,
+            channel,
+            is_ambient,
+            is_sentence,
+            ..SoundInfo::default()
+        };
+
+        if flags == SND_STOP {
+            sound.volume = 0.0;
+            sound.sound_level = 0;
+            return Ok(sound);
+        }
+
+        sound.sequence = if stream.read()? {
+            delta.sequence
+        } else if stream.read()? {
+            delta.sequence.wrapping_add(1)
+        } else {
+            stream.read_int(SOUND_SEQUENCE_BITS)?
+        };
+        sound.volume = read_delta(stream, delta.volume, |stream| {
+            Ok(stream.read_int::<u8>(7)? as f32 / 127.0)
+        })?;
+        sound.sound_level = read_delta(stream, delta.sound_level, |stream| {
+            stream.read_int(MAX_SNDLVL_BITS)
+        })?;
+        sound.pitch = read_delta(stream, delta.pitch, |stream| stream.read())?;
+        if protocol_version > 21 {
+            sound.special_dsp = read_delta(stream, delta.special_dsp, |stream| stream.read())?;
+        }
+        sound.delay = read_delta(stream, delta.delay, |stream| {
+            let mut delay = stream.read_int::<i32>(SOUND_DELAY_BITS)? as f32 / 1000.0;
+            if delay < 0.0 {
+                delay *= 10.0;
+            }
+            Ok(delay - SOUND_DELAY_OFFSET)
+        })?;
+        let read_coord = |stream: &mut Stream| Ok(stream.read_int::<i32>(COORD_BITS)? as f32 * 8.0);
+        sound.origin = Vector {
+            x: read_delta(stream, delta.origin.x, read_coord)?,
+            y: read_delta(stream, delta.origin.y, read_coord)?,
+            z: read_delta(stream, delta.origin.z, read_coord)?,
+        };
+        sound.speaker_entity = read_delta(stream, delta.speaker_entity, |stream| {
+            stream.read_int(MAX_EDICT_BITS + 1)
+        })?;
+
+        Ok(sound)
+    }
+
+    fn write_delta(
+        &self,
+        stream: &mut BitWriteStream<LittleEndian>,
+        delta: &SoundInfo,
+        protocol_version: u32,
+    ) -> ReadResult<()> {
+        write_delta(stream, self.entity, delta.entity, |stream, entity| {
+            let entity = u32::from(entity);
+            let short = entity < 32;
+            short.write(stream)?;
+            entity.write_sized(stream, if short { 5 } else { MAX_EDICT_BITS })
+        })?;
+        write_delta(
+            stream,
+            self.sound_index,
+            delta.sound_index,
+            |stream, index| index.write_sized(stream, Self::sound_index_bits(protocol_version)),
+        )?;
+        write_delta(stream, self.flags, delta.flags, |stream, flags| {
+            flags.write_sized(stream, SND_FLAG_BITS_ENCODE)
+        })?;
+        write_delta(stream, self.channel, delta.channel, |stream, channel| {
+            (channel as u8).write_sized(stream, 3)
+        })?;
+        self.is_ambient.write(stream)?;
+        self.is_sentence.write(stream)?;
+
+        if self.flags == SND_STOP {
+            return Ok(());
+        }
+
+        if self.sequence == delta.sequence {
+            true.write(stream)?;
+        } else if self.sequence == delta.sequence.wrapping_add(1) {
+            (false, true).write(stream)?;
+        } else {
+            (false, false).write(stream)?;
+            self.sequence.write_sized(stream, SOUND_SEQUENCE_BITS)?;
+        }
+        let volume = |volume: f32| (volume * 127.0).round() as u8;
+        write_delta(
+            stream,
+            volume(self.volume),
+            volume(delta.volume),
+            |stream, volume| volume.write_sized(stream, 7),
+        )?;
+        write_delta(
+            stream,
+            self.sound_level,
+            delta.sound_level,
+            |stream, level| level.write_sized(stream, MAX_SNDLVL_BITS),
+        )?;
+        write_delta(stream, self.pitch, delta.pitch, |stream, pitch| {
+            pitch.write(stream)
+        })?;
+        if protocol_version > 21 {
+            write_delta(
+                stream,
+                self.special_dsp,
+                delta.special_dsp,
+                |stream, dsp| dsp.write(stream),
+            )?;
+        }
+        write_delta(stream, self.delay, delta.delay, |stream, delay| {
+            let delay = delay + SOUND_DELAY_OFFSET;
+            let raw = if delay < 0.0 {
+                delay * 100.0
+            } else {
+                delay * 1000.0
+            };
+            (raw.round() as i32).write_sized(stream, SOUND_DELAY_BITS)
+        })?;
+        let write_coord = |stream: &mut BitWriteStream<LittleEndian>, coord: f32| {
+            ((coord / 8.0) as i32).write_sized(stream, COORD_BITS)
+        };
+        write_delta(stream, self.origin.x, delta.origin.x, write_coord)?;
+        write_delta(stream, self.origin.y, delta.origin.y, write_coord)?;
+        write_delta(stream, self.origin.z, delta.origin.z, write_coord)?;
+        write_delta(
+            stream,
+            self.speaker_entity,
+            delta.speaker_entity,
+            |stream, speaker| speaker.write_sized(stream, MAX_EDICT_BITS + 1),
+        )
+    }
+}
+
+#[test]
+fn test_sound_info_roundtrip() {
+    let mut state = ParserState::new(24, |_| false, false);
+    state.handle_string_table(&StringTable {
+        name: "soundprecache".into(),
+        entries: vec![],
+        max_entries: 4096,
+        fixed_user_data_size: None,
+        client_entries: None,
+        compressed: false,
+    });
+    state.handle_string_entry(
+        "soundprecache",
+        2,
+        &StringTableEntry {
+            text: Some("weapons/medigun_heal.wav".into()),
+            extra_data: None,
+        },
+    );
+
+    let sounds = vec![
+        SoundInfo {
+            entity: 5u32.into(),
+            sound_index: 2,
+            name: Some("weapons/medigun_heal.wav".into()),
+            channel: SoundChannel::Weapon,
+            sequence: 12,
+            volume: 1.0,
+            pitch: 100,
+            origin: Vector {
+                x: 128.0,
+                y: -64.0,
+                z: 0.0,
+            },
+            ..SoundInfo::default()
+        },
+        SoundInfo {
+            entity: 120u32.into(),
+            sound_index: 300,
+            flags: SoundFlag::ChangeVolume as u16,
+            channel: SoundChannel::Body,
+            sequence: 13,
+            volume: 0.0,
+            sound_level: 80,
+            delay: 0.4,
+            origin: Vector {
+                x: 128.0,
+                y: -64.0,
+                z: 24.0,
+            },
+            ..SoundInfo::default()
+        },
+        SoundInfo {
+            entity: 120u32.into(),
+            sound_index: 300,
+            flags: SND_STOP,
+            channel: SoundChannel::Body,
+            volume: 0.0,
+            sound_level: 0,
+            ..SoundInfo::default()
+        },
+    ];
+
+    let message = ParseSoundsMessage::from_sounds(&sounds, false, 24).unwrap();
+    assert_eq!(sounds, message.sounds(&state).unwrap());
+    crate::test_roundtrip_write(message);
+}
+
 #[test]
 fn test_parse_sounds_roundtrip() {
     use bitbuffer::BitReadBuffer;
@@ -1,16 +1,23 @@
 use bitbuffer::{BitReadBuffer, BitReadStream, LittleEndian};
 
+pub mod cut;
 pub mod data;
+pub mod diff;
 pub mod gameevent_gen;
 pub mod gamevent;
 pub mod header;
 pub mod lzss;
+pub mod merge;
 pub mod message;
 pub mod packet;
 pub mod parser;
+pub mod repair;
 pub mod sendprop;
 mod sendprop_gen;
+pub mod transform;
 pub mod vector;
+pub mod voice;
+pub mod writer;
 
 pub type Buffer<'a> = BitReadBuffer<'a, LittleEndian>;
 pub type Stream<'a> = BitReadStream<'a, LittleEndian>;
@@ -19,12 +19,21 @@ pub struct MessagePacketMeta {
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(bound(deserialize = "'a: 'static"))]
 pub struct MessagePacket<'a> {
     pub tick: DemoTick,
     pub messages: Vec<Message<'a>>,
     pub meta: MessagePacketMeta,
+    /// The bits after the last message, including any trailing empty messages, these aren't always zeroed
+    #[serde(skip)]
+    pub padding: Option<Stream<'a>>,
+}
+
+impl PartialEq for MessagePacket<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tick == other.tick && self.messages == other.messages && self.meta == other.meta
+    }
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -67,14 +76,22 @@ impl<'a> Parse<'a> for MessagePacket<'a> {
         let mut packet_data = stream.read_bits(length as usize * 8)?;
 
         let mut messages = Vec::with_capacity(8);
+        // start of the trailing empty messages, these are kept as part of the padding
+        let mut padding_start = None;
         while packet_data.bits_left() > 6 {
+            let message_start = packet_data.pos();
             let message_type = MessageType::read(&mut packet_data)?;
+            if message_type == MessageType::Empty {
+                padding_start.get_or_insert(message_start);
+                continue;
+            }
+            padding_start = None;
             #[cfg(feature = "trace")]
             let _span =
                 span!(Level::DEBUG, "reading message", message_type = ?message_type, tick = tick)
                     .entered();
 
-            if state.should_parse_message(message_type) && message_type != MessageType::Empty {
+            if state.should_parse_message(message_type) {
                 #[cfg(feature = "trace")]
                 event!(Level::TRACE, "parsing message");
                 messages.push(Message::from_type(message_type, &mut packet_data, state)?);
@@ -85,10 +102,19 @@ impl<'a> Parse<'a> for MessagePacket<'a> {
             }
         }
 
+        if let Some(padding_start) = padding_start {
+            packet_data.set_pos(padding_start)?;
+        }
+        let padding = match packet_data.bits_left() {
+            0 => None,
+            bits => Some(packet_data.read_bits(bits)?),
+        };
+
         let packet = MessagePacket {
             tick,
             messages,
             meta,
+            padding,
         };
         Ok(packet)
     }
@@ -104,6 +130,13 @@ impl Encode for MessagePacket<'_> {
                 message.encode(stream, state)?;
             }
 
+            // only keep the original padding if it still ends on a byte boundary
+            if let Some(padding) = &self.padding {
+                if (stream.bit_len() + padding.bit_len()) % 8 == 0 {
+                    stream.write_bits(padding)?;
+                }
+            }
+
             Ok(())
         })
     }
@@ -67,7 +67,7 @@ impl Packet<'_> {
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
-#[derive(BitRead, BitWrite, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(BitRead, BitWrite, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[discriminant_bits = 8]
 #[repr(u8)]
 pub enum PacketType {
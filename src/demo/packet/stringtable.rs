@@ -32,12 +32,12 @@ pub struct StringTable<'a> {
 
 impl PartialEq for StringTable<'_> {
     fn eq(&self, other: &Self) -> bool {
-        // ignore `compresses` until we encode compressed
         self.name.eq(&other.name)
             && (self.entries.eq(&other.entries))
             && (self.max_entries.eq(&other.max_entries))
             && (self.fixed_user_data_size.eq(&other.fixed_user_data_size))
             && (self.client_entries.eq(&other.client_entries))
+            && (self.compressed.eq(&other.compressed))
     }
 }
 
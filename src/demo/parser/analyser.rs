@@ -1,15 +1,20 @@
-use crate::demo::data::{DemoTick, ServerTick};
+use crate::demo::data::{DemoTick, ServerTick, SteamId, WeaponInfo};
 use crate::demo::gameevent_gen::{
-    GameEvent, PlayerDeathEvent, PlayerSpawnEvent, TeamPlayRoundWinEvent,
+    GameEvent, PlayerConnectClientEvent, PlayerDeathEvent, PlayerDisconnectEvent, PlayerSpawnEvent,
+    TeamPlayRoundWinEvent, VoteOptionsEvent,
 };
 use crate::demo::message::packetentities::EntityId;
-use crate::demo::message::usermessage::{ChatMessageKind, SayText2Message, UserMessage};
+use crate::demo::message::usermessage::{
+    ChatMessageKind, SayText2Message, UserMessage, VoteFailReason, VoteStartMessage,
+};
 use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::message::MessagePacketMeta;
 use crate::demo::packet::stringtable::StringTableEntry;
 use crate::demo::parser::handler::{BorrowMessageHandler, MessageHandler};
 use crate::demo::vector::Vector;
 use crate::{ParserState, ReadResult, Stream};
 use bitbuffer::{BitWrite, BitWriteStream, Endianness};
+use enumflags2::{bitflags, BitFlags};
 use num_enum::TryFromPrimitive;
 use parse_display::{Display, FromStr};
 use serde::de::Error;
@@ -279,7 +284,7 @@ pub struct UserInfo {
     pub classes: ClassList,
     pub name: String,
     pub user_id: UserId,
-    pub steam_id: String,
+    pub steam_id: SteamId,
     #[serde(skip)]
     pub entity_id: EntityId,
     pub team: Team,
@@ -308,13 +313,246 @@ impl PartialEq for UserInfo {
     }
 }
 
+/// Connection timeline of a single user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub user_id: UserId,
+    /// Tick the user joined, or the first tick the user was seen for users that were already connected
+    pub join_tick: DemoTick,
+    pub leave_tick: Option<DemoTick>,
+    pub disconnect_reason: Option<String>,
+    pub is_bot: bool,
+    pub is_hltv: bool,
+    pub is_replay: bool,
+}
+
+impl Presence {
+    pub fn new(user_id: UserId, tick: DemoTick) -> Self {
+        Presence {
+            user_id,
+            join_tick: tick,
+            ..Presence::default()
+        }
+    }
+
+    pub fn from_user_info(info: &crate::demo::data::UserInfo, tick: DemoTick) -> Self {
+        let mut presence = Presence::new(info.player_info.user_id, tick);
+        presence.update_flags(info);
+        presence
+    }
+
+    fn update_flags(&mut self, info: &crate::demo::data::UserInfo) {
+        self.is_bot |= info.player_info.is_fake_player > 0;
+        self.is_hltv |= info.player_info.is_hl_tv > 0;
+        self.is_replay |= info.player_info.is_replay > 0;
+    }
+
+    pub fn handle_connect(&mut self, event: &PlayerConnectClientEvent, tick: DemoTick) {
+        self.join_tick = self.join_tick.min(tick);
+        self.is_bot |= event.bot > 0;
+    }
+
+    pub fn handle_disconnect(&mut self, event: &PlayerDisconnectEvent, tick: DemoTick) {
+        self.leave_tick = Some(tick);
+        self.disconnect_reason = Some(event.reason.to_string());
+        self.is_bot |= event.bot > 0;
+    }
+
+    /// Whether the user left before the end of the demo
+    pub fn has_left(&self) -> bool {
+        self.leave_tick.is_some()
+    }
+}
+
+/// Flags set in the `death_flags` of a player death
+#[bitflags]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u16)]
+pub enum DeathFlag {
+    /// The killer started dominating the victim
+    Domination = 0x0001,
+    /// The assister started dominating the victim
+    AssisterDomination = 0x0002,
+    /// The killer got revenge on the victim
+    Revenge = 0x0004,
+    /// The assister got revenge on the victim
+    AssisterRevenge = 0x0008,
+    FirstBlood = 0x0010,
+    /// Fake death of a spy using the dead ringer
+    FeignDeath = 0x0020,
+    /// Killed while building or carrying a building
+    Interrupted = 0x0040,
+    Gibbed = 0x0080,
+    /// Killed while in the halloween underworld
+    Purgatory = 0x0100,
+    /// Killed a giant robot in mvm
+    MiniBoss = 0x0200,
+    /// Killed with an australium weapon
+    Australium = 0x0400,
+}
+
+/// The special way a player was killed, `TF_DMG_CUSTOM_*` in the game
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, TryFromPrimitive)]
+#[serde(rename_all = "camelCase")]
+#[repr(u16)]
+pub enum KillType {
+    Normal = 0,
+    Headshot = 1,
+    Backstab = 2,
+    Burning = 3,
+    WrenchFix = 4,
+    Minigun = 5,
+    Suicide = 6,
+    TauntHadouken = 7,
+    BurningFlare = 8,
+    TauntHighNoon = 9,
+    TauntGrandSlam = 10,
+    PenetrateMyTeam = 11,
+    PenetrateAllPlayers = 12,
+    TauntFencing = 13,
+    PenetrateNonBurningTeammate = 14,
+    TauntArrowStab = 15,
+    Telefrag = 16,
+    BurningArrow = 17,
+    FlyingBurn = 18,
+    PumpkinBomb = 19,
+    Decapitation = 20,
+    TauntGrenade = 21,
+    Baseball = 22,
+    ChargeImpact = 23,
+    TauntBarbarianSwing = 24,
+    AirStickyBurst = 25,
+    DefensiveSticky = 26,
+    Pickaxe = 27,
+    RocketDirectHit = 28,
+    TauntUberslice = 29,
+    PlayerSentry = 30,
+    StandardSticky = 31,
+    ShotgunRevengeCrit = 32,
+    TauntEngineerGuitarSmash = 33,
+    Bleeding = 34,
+    GoldWrench = 35,
+    CarriedBuilding = 36,
+    ComboPunch = 37,
+    TauntEngineerArmKill = 38,
+    FishKill = 39,
+    TriggerHurt = 40,
+    DecapitationBoss = 41,
+    StickbombExplosion = 42,
+    AegisRound = 43,
+    FlareExplosion = 44,
+    BootsStomp = 45,
+    Plasma = 46,
+    PlasmaCharged = 47,
+    PlasmaGib = 48,
+    PracticeSticky = 49,
+    EyeballRocket = 50,
+    HeadshotDecapitation = 51,
+    TauntArmageddon = 52,
+    FlarePellet = 53,
+    Cleaver = 54,
+    CleaverCrit = 55,
+    SapperRecorderDeath = 56,
+    MerasmusPlayerBomb = 57,
+    MerasmusGrenade = 58,
+    MerasmusZap = 59,
+    MerasmusDecapitation = 60,
+    CannonballPush = 61,
+    TauntAllClassGuitarRiff = 62,
+    Throwable = 63,
+    ThrowableKill = 64,
+    SpellTeleport = 65,
+    SpellSkeleton = 66,
+    SpellMirv = 67,
+    SpellMeteor = 68,
+    SpellLightning = 69,
+    SpellFireball = 70,
+    SpellMonoculus = 71,
+    SpellBlastJump = 72,
+    SpellBats = 73,
+    SpellTiny = 74,
+    Kart = 75,
+    GiantHammer = 76,
+    RuneReflect = 77,
+    DragonsFuryIgnite = 78,
+    DragonsFuryBonusBurning = 79,
+    SlapKill = 80,
+    Croc = 81,
+    TauntGasBlast = 82,
+    AxtinguisherBoosted = 83,
+    KrampusMelee = 84,
+    KrampusRanged = 85,
+    Unknown = u16::MAX,
+}
+
+impl KillType {
+    pub fn new(number: u16) -> Self {
+        KillType::try_from(number).unwrap_or(KillType::Unknown)
+    }
+
+    /// Whether the kill was the result of a taunt
+    pub fn is_taunt(&self) -> bool {
+        matches!(
+            self,
+            KillType::TauntHadouken
+                | KillType::TauntHighNoon
+                | KillType::TauntGrandSlam
+                | KillType::TauntFencing
+                | KillType::TauntArrowStab
+                | KillType::TauntGrenade
+                | KillType::TauntBarbarianSwing
+                | KillType::TauntUberslice
+                | KillType::TauntEngineerGuitarSmash
+                | KillType::TauntEngineerArmKill
+                | KillType::TauntArmageddon
+                | KillType::TauntAllClassGuitarRiff
+                | KillType::TauntGasBlast
+        )
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, TryFromPrimitive, Default,
+)]
+#[serde(rename_all = "camelCase")]
+#[repr(u16)]
+pub enum CritType {
+    #[default]
+    None = 0,
+    MiniCrit = 1,
+    Crit = 2,
+}
+
+impl CritType {
+    pub fn new(number: u16) -> Self {
+        CritType::try_from(number).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Death {
     pub weapon: String,
     pub victim: UserId,
     pub assister: Option<UserId>,
+    /// The user id of the killer, `0` if the victim was killed by the world
     pub killer: UserId,
     pub tick: DemoTick,
+    #[serde(default)]
+    pub death_flags: u16,
+    /// Raw `customkill` value, see [`Death::kill_type`]
+    #[serde(default)]
+    pub custom_kill: u16,
+    #[serde(default)]
+    pub crit_type: CritType,
+    #[serde(default)]
+    pub weapon_id: u16,
+    /// Number of players the shot penetrated before killing the victim
+    #[serde(default)]
+    pub penetrate_count: u16,
+    /// Whether the killer was rocket or sticky jumping
+    #[serde(default)]
+    pub rocket_jump: bool,
 }
 
 impl Death {
@@ -330,10 +568,262 @@ impl Death {
             killer: UserId::from(event.attacker),
             weapon: event.weapon.to_string(),
             victim: UserId::from(event.user_id),
+            death_flags: event.death_flags,
+            custom_kill: event.custom_kill,
+            crit_type: CritType::new(event.crit_type),
+            weapon_id: event.weapon_id,
+            penetrate_count: event.player_penetrate_count,
+            rocket_jump: event.rocket_jump,
+        }
+    }
+
+    pub fn flags(&self) -> BitFlags<DeathFlag> {
+        BitFlags::from_bits_truncate(self.death_flags)
+    }
+
+    /// Metadata for the weapon used for the kill, if the kill icon is known
+    pub fn weapon_info(&self) -> Option<&'static WeaponInfo> {
+        WeaponInfo::from_kill_icon(&self.weapon)
+    }
+
+    pub fn kill_type(&self) -> KillType {
+        KillType::new(self.custom_kill)
+    }
+
+    /// Fake death of a spy using the dead ringer
+    pub fn is_feign_death(&self) -> bool {
+        self.flags().contains(DeathFlag::FeignDeath)
+    }
+
+    /// The victim killed themselves, either with their own weapon or the kill command
+    pub fn is_suicide(&self) -> bool {
+        self.killer == self.victim || self.kill_type() == KillType::Suicide
+    }
+
+    /// The victim was killed by the world without another player being responsible, like falling to their death
+    pub fn is_world_kill(&self) -> bool {
+        self.killer == UserId::default() && !self.is_suicide()
+    }
+}
+
+#[test]
+fn test_death_types() {
+    let death = Death {
+        weapon: "world".into(),
+        victim: UserId::from(3u16),
+        assister: None,
+        killer: UserId::from(0u16),
+        tick: DemoTick::default(),
+        death_flags: 0,
+        custom_kill: 40,
+        crit_type: CritType::new(0),
+        weapon_id: 0,
+        penetrate_count: 0,
+        rocket_jump: false,
+    };
+    assert_eq!(KillType::TriggerHurt, death.kill_type());
+    assert!(death.is_world_kill());
+    assert!(!death.is_suicide());
+
+    let feign = Death {
+        weapon: "tf_projectile_rocket".into(),
+        killer: UserId::from(5u16),
+        death_flags: (DeathFlag::FeignDeath | DeathFlag::FirstBlood).bits(),
+        custom_kill: 0,
+        crit_type: CritType::new(1),
+        ..death.clone()
+    };
+    assert!(feign.is_feign_death());
+    assert!(!feign.is_world_kill());
+    assert_eq!(CritType::MiniCrit, feign.crit_type);
+
+    let suicide = Death {
+        killer: UserId::from(3u16),
+        custom_kill: 6,
+        ..death
+    };
+    assert!(suicide.is_suicide());
+    assert!(!suicide.is_world_kill());
+    assert_eq!(KillType::Unknown, KillType::new(1000));
+}
+
+/// Voice command from the voice menus
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum VoiceCommand {
+    Medic,
+    Thanks,
+    Go,
+    MoveUp,
+    GoLeft,
+    GoRight,
+    Yes,
+    No,
+    PassToMe,
+    Incoming,
+    Spy,
+    SentryAhead,
+    TeleporterHere,
+    DispenserHere,
+    SentryHere,
+    ActivateCharge,
+    ChargeReady,
+    HelpMe,
+    BattleCry,
+    Cheers,
+    Jeers,
+    Positive,
+    Negative,
+    NiceShot,
+    GoodJob,
+    Unknown { menu: u8, item: u8 },
+}
+
+impl VoiceCommand {
+    pub fn new(menu: u8, item: u8) -> Self {
+        match (menu, item) {
+            (0, 0) => VoiceCommand::Medic,
+            (0, 1) => VoiceCommand::Thanks,
+            (0, 2) => VoiceCommand::Go,
+            (0, 3) => VoiceCommand::MoveUp,
+            (0, 4) => VoiceCommand::GoLeft,
+            (0, 5) => VoiceCommand::GoRight,
+            (0, 6) => VoiceCommand::Yes,
+            (0, 7) => VoiceCommand::No,
+            (0, 8) => VoiceCommand::PassToMe,
+            (1, 0) => VoiceCommand::Incoming,
+            (1, 1) => VoiceCommand::Spy,
+            (1, 2) => VoiceCommand::SentryAhead,
+            (1, 3) => VoiceCommand::TeleporterHere,
+            (1, 4) => VoiceCommand::DispenserHere,
+            (1, 5) => VoiceCommand::SentryHere,
+            (1, 6) => VoiceCommand::ActivateCharge,
+            (1, 7) => VoiceCommand::ChargeReady,
+            (2, 0) => VoiceCommand::HelpMe,
+            (2, 1) => VoiceCommand::BattleCry,
+            (2, 2) => VoiceCommand::Cheers,
+            (2, 3) => VoiceCommand::Jeers,
+            (2, 4) => VoiceCommand::Positive,
+            (2, 5) => VoiceCommand::Negative,
+            (2, 6) => VoiceCommand::NiceShot,
+            (2, 7) => VoiceCommand::GoodJob,
+            (menu, item) => VoiceCommand::Unknown { menu, item },
+        }
+    }
+
+    /// Subtitle as shown in game
+    pub fn text(&self) -> &'static str {
+        match self {
+            VoiceCommand::Medic => "MEDIC!",
+            VoiceCommand::Thanks => "Thanks!",
+            VoiceCommand::Go => "Go! Go! Go!",
+            VoiceCommand::MoveUp => "Move Up!",
+            VoiceCommand::GoLeft => "Go Left",
+            VoiceCommand::GoRight => "Go Right",
+            VoiceCommand::Yes => "Yes",
+            VoiceCommand::No => "No",
+            VoiceCommand::PassToMe => "Pass To Me!",
+            VoiceCommand::Incoming => "Incoming",
+            VoiceCommand::Spy => "Spy!",
+            VoiceCommand::SentryAhead => "Sentry Ahead!",
+            VoiceCommand::TeleporterHere => "Teleporter Here",
+            VoiceCommand::DispenserHere => "Dispenser Here",
+            VoiceCommand::SentryHere => "Sentry Here",
+            VoiceCommand::ActivateCharge => "Activate Charge!",
+            VoiceCommand::ChargeReady => "MEDIC: ÜberCharge Ready",
+            VoiceCommand::HelpMe => "Help!",
+            VoiceCommand::BattleCry => "Battle Cry",
+            VoiceCommand::Cheers => "Cheers",
+            VoiceCommand::Jeers => "Jeers",
+            VoiceCommand::Positive => "Positive",
+            VoiceCommand::Negative => "Negative",
+            VoiceCommand::NiceShot => "Nice Shot",
+            VoiceCommand::GoodJob => "Good Job",
+            VoiceCommand::Unknown { .. } => "",
         }
     }
 }
 
+#[test]
+fn test_voice_command() {
+    assert_eq!(VoiceCommand::Medic, VoiceCommand::new(0, 0));
+    assert_eq!(VoiceCommand::Spy, VoiceCommand::new(1, 1));
+    assert_eq!(VoiceCommand::ChargeReady, VoiceCommand::new(1, 7));
+    assert_eq!(VoiceCommand::GoodJob, VoiceCommand::new(2, 7));
+    assert_eq!(
+        VoiceCommand::Unknown { menu: 3, item: 0 },
+        VoiceCommand::new(3, 0)
+    );
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VoiceCall {
+    pub user: Option<UserId>,
+    pub command: VoiceCommand,
+    pub tick: DemoTick,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteCast {
+    pub user: Option<UserId>,
+    pub option: u8,
+    pub tick: DemoTick,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum VoteResult {
+    Pending,
+    Passed,
+    Failed { reason: VoteFailReason },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Vote {
+    /// Localization key of the vote issue, e.g. `#TF_vote_kick_player_other`
+    pub issue: String,
+    /// Issue parameter, like the name of the player being kicked or the map being voted for
+    pub details: String,
+    /// Team that can vote or `Other` if everyone can vote
+    pub team: Team,
+    pub initiator: Option<UserId>,
+    pub target: Option<UserId>,
+    pub options: Vec<String>,
+    pub votes: Vec<VoteCast>,
+    pub counts: Vec<u8>,
+    pub potential_votes: u8,
+    pub result: VoteResult,
+    pub start_tick: DemoTick,
+    pub end_tick: Option<DemoTick>,
+}
+
+impl Vote {
+    pub fn is_pending(&self) -> bool {
+        self.result == VoteResult::Pending
+    }
+
+    /// Get the name of the option a vote was cast for
+    pub fn option_name(&self, vote: &VoteCast) -> Option<&str> {
+        self.options.get(vote.option as usize).map(String::as_str)
+    }
+}
+
+fn vote_options(event: &VoteOptionsEvent) -> Vec<String> {
+    [
+        &event.option_1,
+        &event.option_2,
+        &event.option_3,
+        &event.option_4,
+        &event.option_5,
+    ]
+    .iter()
+    .take(event.count as usize)
+    .map(|option| option.to_string())
+    .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Round {
     pub winner: Team,
@@ -361,6 +851,8 @@ pub struct World {
 pub struct Analyser {
     state: MatchState,
     user_id_map: HashMap<EntityId, UserId>,
+    tick: DemoTick,
+    pending_vote_options: Option<Vec<String>>,
 }
 
 impl MessageHandler for Analyser {
@@ -408,6 +900,15 @@ impl MessageHandler for Analyser {
         }
     }
 
+    fn handle_packet_meta(
+        &mut self,
+        tick: DemoTick,
+        _meta: &MessagePacketMeta,
+        _parser_state: &ParserState,
+    ) {
+        self.tick = tick;
+    }
+
     fn into_output(self, _state: &ParserState) -> Self::Output {
         self.state
     }
@@ -425,19 +926,76 @@ impl Analyser {
     }
 
     fn handle_user_message(&mut self, message: &UserMessage, tick: DemoTick) {
-        if let UserMessage::SayText2(text_message) = message {
-            if text_message.kind == ChatMessageKind::NameChange {
-                if let Some(from) = text_message.from.clone() {
-                    self.change_name(from.into(), text_message.plain_text());
+        match message {
+            UserMessage::SayText2(text_message) => {
+                if text_message.kind == ChatMessageKind::NameChange {
+                    if let Some(from) = text_message.from.clone() {
+                        self.change_name(from.into(), text_message.plain_text());
+                    }
+                } else {
+                    self.state
+                        .chat
+                        .push(ChatMessage::from_message(text_message, tick));
+                }
+            }
+            UserMessage::VoiceSubtitle(message) => {
+                let user = self.user_for_entity(EntityId::from(message.client as u32));
+                self.state.voice_commands.push(VoiceCall {
+                    user,
+                    command: VoiceCommand::new(message.menu, message.item),
+                    tick,
+                });
+            }
+            UserMessage::VoteStart(message) => self.start_vote(message, tick),
+            UserMessage::VotePass(_) => {
+                if let Some(vote) = self.pending_vote() {
+                    vote.result = VoteResult::Passed;
+                    vote.end_tick = Some(tick);
+                }
+            }
+            UserMessage::VoteFailed(message) => {
+                if let Some(vote) = self.pending_vote() {
+                    vote.result = VoteResult::Failed {
+                        reason: VoteFailReason::new(message.reason),
+                    };
+                    vote.end_tick = Some(tick);
                 }
-            } else {
-                self.state
-                    .chat
-                    .push(ChatMessage::from_message(text_message, tick));
             }
+            _ => {}
         }
     }
 
+    fn start_vote(&mut self, message: &VoteStartMessage, tick: DemoTick) {
+        let vote = Vote {
+            issue: message.issue.to_string(),
+            details: message.details.to_string(),
+            team: Team::new(message.team),
+            initiator: self.user_for_entity(message.initiator),
+            // votes without a target have a target of 0
+            target: message
+                .target
+                .filter(|target| u32::from(*target) != 0)
+                .and_then(|target| self.user_for_entity(target)),
+            options: self.pending_vote_options.take().unwrap_or_default(),
+            votes: Vec::new(),
+            counts: Vec::new(),
+            potential_votes: 0,
+            result: VoteResult::Pending,
+            start_tick: tick,
+            end_tick: None,
+        };
+        self.state.votes.push(vote);
+    }
+
+    fn pending_vote(&mut self) -> Option<&mut Vote> {
+        self.state.votes.last_mut().filter(|vote| vote.is_pending())
+    }
+
+    /// Get the user currently in an entity slot, slots are re-used after a player leaves
+    fn user_for_entity(&self, entity: EntityId) -> Option<UserId> {
+        self.user_id_map.get(&entity).copied()
+    }
+
     fn change_name(&mut self, from: String, to: String) {
         if let Some(user) = self.state.users.values_mut().find(|user| user.name == from) {
             user.name = to;
@@ -461,6 +1019,52 @@ impl Analyser {
                     self.state.rounds.push(Round::from_event(event, tick))
                 }
             }
+            GameEvent::PlayerConnectClient(event) => {
+                let user_id = UserId::from(event.user_id);
+                self.state
+                    .presence
+                    .entry(user_id)
+                    .or_insert_with(|| Presence::new(user_id, tick))
+                    .handle_connect(event, tick);
+            }
+            GameEvent::PlayerDisconnect(event) => {
+                let user_id = UserId::from(event.user_id);
+                self.state
+                    .presence
+                    .entry(user_id)
+                    .or_insert_with(|| Presence::new(user_id, tick))
+                    .handle_disconnect(event, tick);
+            }
+            GameEvent::VoteOptions(event) => {
+                let options = vote_options(event);
+                match self.pending_vote() {
+                    Some(vote) if vote.options.is_empty() => vote.options = options,
+                    _ => self.pending_vote_options = Some(options),
+                }
+            }
+            GameEvent::VoteCast(event) => {
+                let user = self.user_for_entity(EntityId::from(event.entity_id));
+                if let Some(vote) = self.pending_vote() {
+                    vote.votes.push(VoteCast {
+                        user,
+                        option: event.vote_option,
+                        tick,
+                    });
+                }
+            }
+            GameEvent::VoteChanged(event) => {
+                if let Some(vote) = self.pending_vote() {
+                    vote.counts = vec![
+                        event.vote_option_1,
+                        event.vote_option_2,
+                        event.vote_option_3,
+                        event.vote_option_4,
+                        event.vote_option_5,
+                    ];
+                    vote.counts.truncate(vote.options.len().max(2));
+                    vote.potential_votes = event.potential_votes;
+                }
+            }
             _ => {}
         }
     }
@@ -474,6 +1078,14 @@ impl Analyser {
         if let Some(user_info) =
             crate::demo::data::UserInfo::parse_from_string_table(index as u16, text, data)?
         {
+            let tick = self.tick;
+            self.user_id_map
+                .insert(user_info.entity_id, user_info.player_info.user_id);
+            self.state
+                .presence
+                .entry(user_info.player_info.user_id)
+                .and_modify(|presence| presence.update_flags(&user_info))
+                .or_insert_with(|| Presence::from_user_info(&user_info, tick));
             self.state
                 .users
                 .entry(user_info.player_info.user_id.into())
@@ -492,8 +1104,200 @@ impl Analyser {
 pub struct MatchState {
     pub chat: Vec<ChatMessage>,
     pub users: BTreeMap<UserId, UserInfo>,
+    /// All kills as shown in the kill feed, including the fake deaths of dead ringer spies (see [`Death::is_feign_death`])
     pub deaths: Vec<Death>,
     pub rounds: Vec<Round>,
     pub start_tick: ServerTick,
     pub interval_per_tick: f32,
+    #[serde(default)]
+    pub presence: BTreeMap<UserId, Presence>,
+    #[serde(default)]
+    pub votes: Vec<Vote>,
+    #[serde(default)]
+    pub voice_commands: Vec<VoiceCall>,
+}
+
+#[test]
+fn test_feign_deaths_kept() {
+    let mut analyser = Analyser::new();
+    let feign = crate::test_util::death_event(3, 5, DeathFlag::FeignDeath as u16, 0);
+    analyser.handle_event(
+        &GameEvent::PlayerDeath(Box::new(feign)),
+        DemoTick::from(1u32),
+    );
+    analyser.handle_event(
+        &GameEvent::PlayerDeath(Box::new(crate::test_util::death_event(3, 5, 0, 0))),
+        DemoTick::from(2u32),
+    );
+    assert_eq!(2, analyser.state.deaths.len());
+    assert!(analyser.state.deaths[0].is_feign_death());
+    assert!(!analyser.state.deaths[1].is_feign_death());
+}
+
+#[test]
+fn test_vote_users_reused_slot() {
+    use crate::demo::gameevent_gen::VoteCastEvent;
+    use crate::test_util::{game_event, user_info_entry};
+
+    let state = ParserState::new(24, Analyser::does_handle, false);
+    let mut analyser = Analyser::new();
+    analyser.handle_string_entry("userinfo", 2, &user_info_entry(2, 3), &state);
+    analyser.handle_string_entry("userinfo", 3, &user_info_entry(4, 4), &state);
+    // user 2 leaves and user 5 joins in the same slot
+    analyser.handle_string_entry("userinfo", 2, &user_info_entry(5, 3), &state);
+
+    analyser.handle_message(
+        &Message::UserMessage(UserMessage::VoteStart(VoteStartMessage {
+            team: 2,
+            vote_index: Some(1),
+            initiator: 3u32.into(),
+            issue: "#TF_vote_kick_player_other".into(),
+            details: "player 4".into(),
+            yes_no: true,
+            target: Some(4u32.into()),
+        })),
+        DemoTick::from(1u32),
+        &state,
+    );
+    analyser.handle_message(
+        &game_event(GameEvent::VoteCast(VoteCastEvent {
+            vote_option: 0,
+            team: 2,
+            entity_id: 3,
+        })),
+        DemoTick::from(2u32),
+        &state,
+    );
+
+    let vote = &analyser.state.votes[0];
+    assert_eq!(Some(UserId::from(5u16)), vote.initiator);
+    assert_eq!(Some(UserId::from(4u16)), vote.target);
+    assert_eq!(Some(UserId::from(5u16)), vote.votes[0].user);
+}
+
+#[test]
+fn test_voice_command_reused_slot() {
+    use crate::demo::message::usermessage::VoiceSubtitleMessage;
+    use crate::test_util::user_info_entry;
+
+    let state = ParserState::new(24, Analyser::does_handle, false);
+    let mut analyser = Analyser::new();
+    let medic = |client| {
+        Message::UserMessage(UserMessage::VoiceSubtitle(VoiceSubtitleMessage {
+            client,
+            menu: 0,
+            item: 0,
+        }))
+    };
+
+    analyser.handle_string_entry("userinfo", 2, &user_info_entry(2, 3), &state);
+    analyser.handle_message(&medic(3), DemoTick::from(1u32), &state);
+    // user 2 is substituted by user 5 in the same slot
+    analyser.handle_string_entry("userinfo", 2, &user_info_entry(5, 3), &state);
+    analyser.handle_message(&medic(3), DemoTick::from(2u32), &state);
+
+    assert_eq!(
+        vec![Some(UserId::from(2u16)), Some(UserId::from(5u16))],
+        analyser
+            .state
+            .voice_commands
+            .iter()
+            .map(|call| call.user)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_vote_flow() {
+    use crate::demo::gameevent_gen::{VoteCastEvent, VoteChangedEvent};
+    use crate::demo::message::usermessage::{
+        VoteFailedMessage, VoteIssue, VotePassMessage, VoteSetupMessage,
+    };
+    use crate::test_util::{game_event, user_info_entry};
+
+    let state = ParserState::new(24, Analyser::does_handle, false);
+    let mut analyser = Analyser::new();
+    analyser.handle_string_entry("userinfo", 2, &user_info_entry(2, 3), &state);
+    analyser.handle_string_entry("userinfo", 3, &user_info_entry(4, 4), &state);
+
+    let vote_start = |issue: &str, target: u32| {
+        Message::UserMessage(UserMessage::VoteStart(VoteStartMessage {
+            team: 2,
+            vote_index: Some(1),
+            initiator: 3u32.into(),
+            issue: issue.into(),
+            details: "".into(),
+            yes_no: true,
+            target: Some(target.into()),
+        }))
+    };
+    let vote_cast = |entity_id, vote_option| {
+        game_event(GameEvent::VoteCast(VoteCastEvent {
+            vote_option,
+            team: 2,
+            entity_id,
+        }))
+    };
+    let messages = [
+        Message::UserMessage(UserMessage::VoteSetup(VoteSetupMessage {
+            count: 1,
+            issues: vec![VoteIssue {
+                name: "ChangeLevel".into(),
+                translation: "#TF_vote_changelevel".into(),
+                active: 1,
+            }],
+        })),
+        vote_start("#TF_vote_changelevel", 0),
+        vote_cast(3, 0),
+        vote_cast(4, 1),
+        game_event(GameEvent::VoteChanged(VoteChangedEvent {
+            vote_option_1: 1,
+            vote_option_2: 1,
+            vote_option_3: 0,
+            vote_option_4: 0,
+            vote_option_5: 0,
+            potential_votes: 2,
+        })),
+        Message::UserMessage(UserMessage::VotePass(VotePassMessage {
+            team: 2,
+            vote_index: Some(1),
+            issue: "#TF_vote_passed_changelevel".into(),
+            details: "".into(),
+        })),
+        vote_start("#TF_vote_kick_player_other", 4),
+        vote_cast(3, 0),
+        Message::UserMessage(UserMessage::VoteFailed(VoteFailedMessage {
+            team: 2,
+            vote_index: Some(2),
+            reason: 99,
+        })),
+    ];
+    for (tick, message) in messages.iter().enumerate() {
+        analyser.handle_message(message, DemoTick::from(tick as u32), &state);
+    }
+
+    let votes = &analyser.state.votes;
+    assert_eq!(2, votes.len());
+    assert_eq!(Some(UserId::from(2u16)), votes[0].initiator);
+    assert_eq!(None, votes[0].target);
+    assert_eq!(
+        vec![(Some(UserId::from(2u16)), 0), (Some(UserId::from(4u16)), 1)],
+        votes[0]
+            .votes
+            .iter()
+            .map(|vote| (vote.user, vote.option))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(vec![1, 1], votes[0].counts);
+    assert_eq!(VoteResult::Passed, votes[0].result);
+    assert_eq!(Some(DemoTick::from(5u32)), votes[0].end_tick);
+
+    assert_eq!(Some(UserId::from(4u16)), votes[1].target);
+    assert_eq!(1, votes[1].votes.len());
+    assert_eq!(
+        VoteResult::Failed {
+            reason: VoteFailReason::Unknown(99)
+        },
+        votes[1].result
+    );
 }
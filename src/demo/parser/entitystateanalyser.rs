@@ -0,0 +1,109 @@
+use crate::demo::data::DemoTick;
+use crate::demo::message::packetentities::{
+    EntityId, PacketEntitiesMessage, PacketEntity, UpdateType,
+};
+use crate::demo::message::{Message, MessageType};
+use crate::demo::parser::handler::{BorrowMessageHandler, MessageHandler};
+use crate::ParserState;
+use std::collections::BTreeMap;
+
+/// The full state of all entities in the pvs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityState {
+    /// All entities with all their props, including the props from the baselines
+    pub entities: BTreeMap<EntityId, PacketEntity>,
+    pub max_entries: u16,
+}
+
+impl EntityState {
+    /// Create a full (non-delta) entity update containing all entities
+    ///
+    /// The update also sets the entities as the new baseline, the same as the first update the server sends
+    pub fn full_update(&self) -> PacketEntitiesMessage {
+        PacketEntitiesMessage {
+            entities: self.entities.values().cloned().collect(),
+            removed_entities: Vec::new(),
+            max_entries: self.max_entries,
+            delta: None,
+            base_line: 0,
+            updated_base_line: true,
+        }
+    }
+}
+
+/// Track the full state of all entities
+#[derive(Debug, Default)]
+pub struct EntityStateAnalyser {
+    state: EntityState,
+}
+
+impl MessageHandler for EntityStateAnalyser {
+    type Output = EntityState;
+
+    fn does_handle(message_type: MessageType) -> bool {
+        matches!(message_type, MessageType::PacketEntities)
+    }
+
+    fn handle_message(&mut self, message: &Message, _tick: DemoTick, parser_state: &ParserState) {
+        if let Message::PacketEntities(message) = message {
+            self.handle_packet_entities(message, parser_state);
+        }
+    }
+
+    fn into_output(self, _state: &ParserState) -> Self::Output {
+        self.state
+    }
+}
+
+impl BorrowMessageHandler for EntityStateAnalyser {
+    fn borrow_output(&self, _state: &ParserState) -> &Self::Output {
+        &self.state
+    }
+}
+
+impl EntityStateAnalyser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn handle_packet_entities(
+        &mut self,
+        message: &PacketEntitiesMessage,
+        parser_state: &ParserState,
+    ) {
+        let entities = &mut self.state.entities;
+        if message.delta.is_none() {
+            entities.clear();
+        }
+        self.state.max_entries = message.max_entries;
+
+        for entity in &message.entities {
+            match entity.update_type {
+                UpdateType::Enter => {
+                    let props = entity.props(parser_state).collect();
+                    entities.insert(
+                        entity.entity_index,
+                        PacketEntity {
+                            props,
+                            delta: None,
+                            baseline_index: 0,
+                            ..entity.clone()
+                        },
+                    );
+                }
+                UpdateType::Preserve => {
+                    if let Some(existing) = entities.get_mut(&entity.entity_index) {
+                        existing.apply_update(&entity.props);
+                    }
+                }
+                UpdateType::Leave | UpdateType::Delete => {
+                    entities.remove(&entity.entity_index);
+                }
+            }
+        }
+
+        for removed in &message.removed_entities {
+            entities.remove(removed);
+        }
+    }
+}
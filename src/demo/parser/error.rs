@@ -35,6 +35,8 @@ pub enum ParseError {
     },
     #[error(display = "Malformed demo file: {}", _0)]
     InvalidDemo(&'static str),
+    #[error(display = "Demos can't be combined: {}", _0)]
+    IncompatibleDemos(&'static str),
     #[error(display = "Packet identifier is invalid: {}", _0)]
     InvalidPacketType(u8),
     #[error(display = "Message identifier is invalid: {}", _0)]
@@ -1,4 +1,4 @@
-use crate::demo::data::DemoTick;
+use crate::demo::data::{DemoTick, WeaponInfo};
 use crate::demo::gameevent_gen::{ObjectDestroyedEvent, PlayerDeathEvent};
 use crate::demo::gamevent::GameEvent;
 use crate::demo::message::gameevent::GameEventMessage;
@@ -253,6 +253,11 @@ impl Kill {
             tick,
         }
     }
+
+    /// Metadata for the weapon used for the kill, if the kill icon is known
+    pub fn weapon_info(&self) -> Option<&'static WeaponInfo> {
+        WeaponInfo::from_kill_icon(&self.weapon)
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
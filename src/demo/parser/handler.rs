@@ -1,3 +1,4 @@
+use crate::demo::message::voice::{ParseSoundsMessage, SoundInfo};
 use crate::demo::message::{Message, MessageType};
 use crate::demo::packet::datatable::{ParseSendTable, ServerClass};
 use crate::demo::packet::stringtable::{StringTable, StringTableEntry};
@@ -45,6 +46,9 @@ pub trait MessageHandler {
     ) {
     }
 
+    /// Called for every sound in a `ParseSounds` message, if the handler handles `MessageType::ParseSounds`
+    fn handle_sound(&mut self, _sound: &SoundInfo, _tick: DemoTick, _parser_state: &ParserState) {}
+
     fn into_output(self, state: &ParserState) -> Self::Output;
 }
 
@@ -152,8 +156,7 @@ impl<'a, T: MessageHandler> DemoHandler<'a, T> {
     }
 
     fn handle_string_table(&mut self, table: StringTable<'a>) {
-        self.state_handler
-            .handle_string_table_meta(table.get_table_meta());
+        self.state_handler.handle_string_table(&table);
         for (entry_index, entry) in table.entries.into_iter() {
             let entry_index = entry_index as usize;
             self.state_handler
@@ -197,10 +200,27 @@ impl<'a, T: MessageHandler> DemoHandler<'a, T> {
         if T::does_handle(message_type) {
             self.analyser
                 .handle_message(&message, tick, &self.state_handler);
+            if let Message::ParseSounds(message) = &message {
+                self.handle_sounds(message, tick);
+            }
         }
         self.state_handler.handle_message(message, tick);
     }
 
+    fn handle_sounds(&mut self, message: &ParseSoundsMessage, tick: DemoTick) {
+        match message.sounds(&self.state_handler) {
+            Ok(sounds) => {
+                for sound in sounds.iter() {
+                    self.analyser.handle_sound(sound, tick, &self.state_handler);
+                }
+            }
+            Err(_e) => {
+                #[cfg(feature = "trace")]
+                tracing::warn!(error = display(_e), "failed to decode sounds");
+            }
+        }
+    }
+
     pub fn into_output(self) -> T::Output {
         self.analyser.into_output(&self.state_handler)
     }
@@ -13,12 +13,17 @@ pub use crate::demo::parser::state::ParserState;
 use crate::Stream;
 
 pub mod analyser;
+pub mod entitystateanalyser;
 pub mod error;
 pub mod gamestateanalyser;
 pub mod handler;
 pub mod messagetypeanalyser;
+pub mod pickupanalyser;
 pub mod player_summary_analyzer;
+pub mod spyanalyser;
 pub mod state;
+pub mod stringtablestore;
+pub mod voiceanalyser;
 
 pub use self::error::*;
 use crate::demo::parser::handler::BorrowMessageHandler;
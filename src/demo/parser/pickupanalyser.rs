@@ -0,0 +1,525 @@
+use crate::demo::data::{DemoTick, ModelType, PickupSize, UserInfo};
+use crate::demo::gameevent_gen::{ItemPickupEvent, PlayerHealOnHitEvent};
+use crate::demo::gamevent::GameEvent;
+use crate::demo::message::packetentities::{EntityId, PacketEntity, UpdateType};
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::stringtable::StringTableEntry;
+use crate::demo::parser::analyser::UserId;
+use crate::demo::parser::handler::MessageHandler;
+use crate::demo::sendprop::SendPropIdentifier;
+use crate::demo::vector::Vector;
+use crate::ParserState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+/// Entity effect flag set while an item is waiting to respawn
+const EF_NODRAW: i64 = 0x20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PickupKind {
+    HealthKit,
+    AmmoPack,
+}
+
+/// A health kit or ammo pack in the world
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pickup {
+    pub entity: EntityId,
+    pub kind: PickupKind,
+    pub size: PickupSize,
+    /// Ammo packs dropped by players, these don't respawn
+    pub dropped: bool,
+    pub position: Vector,
+    /// Whether the pickup can currently be picked up or is waiting to respawn
+    pub available: bool,
+    /// Ticks at which the pickup was taken
+    pub taken: Vec<DemoTick>,
+    /// Ticks at which the pickup became available again
+    pub respawned: Vec<DemoTick>,
+    /// Tick at which the pickup was removed from the world
+    pub removed: Option<DemoTick>,
+}
+
+/// A player picking up a health kit or ammo pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickupEvent {
+    pub tick: DemoTick,
+    pub user: UserId,
+    pub kind: PickupKind,
+    pub size: PickupSize,
+    /// Health gained from the pickup, zero for ammo packs
+    pub health: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct PickupCounts {
+    pub small: u32,
+    pub medium: u32,
+    pub full: u32,
+}
+
+impl PickupCounts {
+    fn add(&mut self, size: PickupSize) {
+        match size {
+            PickupSize::Small => self.small += 1,
+            PickupSize::Medium => self.medium += 1,
+            PickupSize::Full => self.full += 1,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.small + self.medium + self.full
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerPickups {
+    pub health_kits: PickupCounts,
+    pub ammo_packs: PickupCounts,
+    pub health_gained: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Pickups {
+    /// The pickups currently in the world
+    pub pickups: BTreeMap<EntityId, Pickup>,
+    /// Pickups that were removed from the world, like dropped ammo packs that were picked up
+    pub removed: Vec<Pickup>,
+    pub events: Vec<PickupEvent>,
+    pub players: BTreeMap<UserId, PlayerPickups>,
+}
+
+/// Track health kits and ammo packs and the players picking them up
+///
+/// Pickups of ammo packs dropped by players are not reported by the server and only show up
+/// as the dropped pack being removed, see [`Pickups::removed`].
+#[derive(Debug, Default)]
+pub struct PickupAnalyser {
+    state: Pickups,
+    tick: DemoTick,
+    user_ids: HashMap<EntityId, UserId>,
+    /// Heals from the current tick that haven't been matched to a pickup yet
+    pending_heals: Vec<(EntityId, u16)>,
+}
+
+impl MessageHandler for PickupAnalyser {
+    type Output = Pickups;
+
+    fn does_handle(message_type: MessageType) -> bool {
+        matches!(
+            message_type,
+            MessageType::PacketEntities | MessageType::GameEvent
+        )
+    }
+
+    fn handle_message(&mut self, message: &Message, tick: DemoTick, parser_state: &ParserState) {
+        if tick != self.tick {
+            self.tick = tick;
+            self.pending_heals.clear();
+        }
+
+        match message {
+            Message::PacketEntities(message) => {
+                for entity in &message.entities {
+                    self.handle_entity(entity, parser_state);
+                }
+                for removed in &message.removed_entities {
+                    self.remove_pickup(*removed);
+                }
+            }
+            Message::GameEvent(message) => match &message.event {
+                GameEvent::ItemPickup(event) => self.handle_item_pickup(event),
+                GameEvent::PlayerHealOnHit(event) => self.handle_heal(event),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn handle_string_entry(
+        &mut self,
+        table: &str,
+        index: usize,
+        entry: &StringTableEntry,
+        _parser_state: &ParserState,
+    ) {
+        if table == "userinfo" {
+            if let Ok(Some(user_info)) = UserInfo::parse_from_string_table(
+                index as u16,
+                entry.text.as_deref(),
+                entry.extra_data.as_ref().map(|data| data.data.clone()),
+            ) {
+                self.user_ids
+                    .insert(user_info.entity_id, user_info.player_info.user_id);
+            }
+        }
+    }
+
+    fn into_output(self, _state: &ParserState) -> Self::Output {
+        self.state
+    }
+}
+
+impl PickupAnalyser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn handle_entity(&mut self, entity: &PacketEntity, parser_state: &ParserState) {
+        const ORIGIN_PROP: SendPropIdentifier =
+            SendPropIdentifier::new("DT_BaseEntity", "m_vecOrigin");
+        const EFFECTS_PROP: SendPropIdentifier =
+            SendPropIdentifier::new("DT_BaseEntity", "m_fEffects");
+
+        if entity.update_type == UpdateType::Delete {
+            self.remove_pickup(entity.entity_index);
+            return;
+        }
+
+        // pickups re-entering the pvs keep their history
+        if entity.update_type == UpdateType::Enter
+            && !self.state.pickups.contains_key(&entity.entity_index)
+        {
+            let class_name = parser_state
+                .server_classes
+                .get(usize::from(entity.server_class))
+                .map(|class| class.name.as_str())
+                .unwrap_or_default();
+            let (kind, size, dropped) = match (class_name, entity.model_type(parser_state)) {
+                ("CTFAmmoPack", _) => (PickupKind::AmmoPack, PickupSize::Medium, true),
+                (_, Some(ModelType::HealthKit(size))) => (PickupKind::HealthKit, size, false),
+                (_, Some(ModelType::AmmoPack(size))) => (PickupKind::AmmoPack, size, false),
+                _ => return,
+            };
+            self.state.pickups.insert(
+                entity.entity_index,
+                Pickup {
+                    entity: entity.entity_index,
+                    kind,
+                    size,
+                    dropped,
+                    position: Vector::default(),
+                    available: true,
+                    taken: Vec::new(),
+                    respawned: Vec::new(),
+                    removed: None,
+                },
+            );
+        }
+
+        let pickup = match self.state.pickups.get_mut(&entity.entity_index) {
+            Some(pickup) => pickup,
+            None => return,
+        };
+
+        for prop in entity.props(parser_state) {
+            match prop.identifier {
+                ORIGIN_PROP => pickup.position = Vector::try_from(&prop.value).unwrap_or_default(),
+                EFFECTS_PROP => {
+                    let effects = i64::try_from(&prop.value).unwrap_or_default();
+                    let available = effects & EF_NODRAW == 0;
+                    if available != pickup.available && entity.update_type != UpdateType::Enter {
+                        if available {
+                            pickup.respawned.push(self.tick);
+                        } else {
+                            pickup.taken.push(self.tick);
+                        }
+                    }
+                    pickup.available = available;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn remove_pickup(&mut self, entity: EntityId) {
+        if let Some(mut pickup) = self.state.pickups.remove(&entity) {
+            pickup.removed = Some(self.tick);
+            self.state.removed.push(pickup);
+        }
+    }
+
+    fn handle_item_pickup(&mut self, event: &ItemPickupEvent) {
+        let (kind, size) = match pickup_item(event.item.as_ref()) {
+            Some(item) => item,
+            None => return,
+        };
+        let user = UserId::from(event.user_id);
+
+        let health = if kind == PickupKind::HealthKit {
+            let entity = self
+                .user_ids
+                .iter()
+                .find(|(_, user_id)| **user_id == user)
+                .map(|(entity, _)| *entity);
+            match self
+                .pending_heals
+                .iter()
+                .position(|(heal_entity, _)| Some(*heal_entity) == entity)
+            {
+                Some(index) => self.pending_heals.remove(index).1,
+                None => 0,
+            }
+        } else {
+            0
+        };
+
+        let player = self.state.players.entry(user).or_default();
+        match kind {
+            PickupKind::HealthKit => player.health_kits.add(size),
+            PickupKind::AmmoPack => player.ammo_packs.add(size),
+        }
+        player.health_gained += health as u32;
+
+        self.state.events.push(PickupEvent {
+            tick: self.tick,
+            user,
+            kind,
+            size,
+            health,
+        });
+    }
+
+    fn handle_heal(&mut self, event: &PlayerHealOnHitEvent) {
+        let entity = EntityId::from(event.ent_index as u32);
+        let user = self.user_ids.get(&entity).copied();
+
+        // the heal event can be send after the pickup event
+        let tick = self.tick;
+        let pickup = self.state.events.iter_mut().rev().find(|pickup| {
+            pickup.tick == tick
+                && pickup.kind == PickupKind::HealthKit
+                && pickup.health == 0
+                && Some(pickup.user) == user
+        });
+        match pickup {
+            Some(pickup) => {
+                pickup.health = event.amount;
+                if let Some(player) = self.state.players.get_mut(&pickup.user) {
+                    player.health_gained += event.amount as u32;
+                }
+            }
+            None => self.pending_heals.push((entity, event.amount)),
+        }
+    }
+}
+
+/// Get the type of pickup from the class name of the item
+fn pickup_item(item: &str) -> Option<(PickupKind, PickupSize)> {
+    let kind = if item.starts_with("item_healthkit") {
+        PickupKind::HealthKit
+    } else if item.starts_with("item_ammopack") || item == "tf_ammo_pack" {
+        PickupKind::AmmoPack
+    } else {
+        return None;
+    };
+    let size = if item.ends_with("_small") {
+        PickupSize::Small
+    } else if item.ends_with("_full") {
+        PickupSize::Full
+    } else {
+        PickupSize::Medium
+    };
+    Some((kind, size))
+}
+
+#[test]
+fn test_pickup_item() {
+    assert_eq!(
+        Some((PickupKind::HealthKit, PickupSize::Small)),
+        pickup_item("item_healthkit_small")
+    );
+    assert_eq!(
+        Some((PickupKind::HealthKit, PickupSize::Full)),
+        pickup_item("item_healthkit_full")
+    );
+    assert_eq!(
+        Some((PickupKind::AmmoPack, PickupSize::Medium)),
+        pickup_item("item_ammopack_medium")
+    );
+    assert_eq!(None, pickup_item("item_teamflag"));
+}
+
+#[cfg(test)]
+fn test_state() -> ParserState {
+    use crate::test_util::{parser_state, string_table};
+
+    let mut state = parser_state(PickupAnalyser::does_handle, &["CTFItem", "CTFAmmoPack"]);
+    string_table(
+        &mut state,
+        "modelprecache",
+        &[(1, "models/items/medkit_small.mdl")],
+    );
+    state
+}
+
+/// A pickup entity with the small medkit model, with the effects set if given
+#[cfg(test)]
+fn test_pickup_entity(
+    index: u32,
+    class: u16,
+    update_type: UpdateType,
+    effects: Option<i64>,
+) -> PacketEntity {
+    use crate::demo::sendprop::SendPropValue;
+
+    let mut props = vec![(
+        SendPropIdentifier::new("DT_BaseEntity", "m_nModelIndex"),
+        SendPropValue::Integer(1),
+    )];
+    props.extend(effects.map(|effects| {
+        (
+            SendPropIdentifier::new("DT_BaseEntity", "m_fEffects"),
+            SendPropValue::Integer(effects),
+        )
+    }));
+    crate::test_util::packet_entity(index, class, update_type, props)
+}
+
+#[test]
+fn test_pickup_respawn() {
+    use crate::test_util::packet_entities;
+
+    let state = test_state();
+    let mut analyser = PickupAnalyser::new();
+
+    analyser.handle_message(
+        &packet_entities(
+            vec![
+                test_pickup_entity(10, 0, UpdateType::Enter, Some(0)),
+                test_pickup_entity(11, 1, UpdateType::Enter, None),
+            ],
+            vec![],
+        ),
+        DemoTick::from(1u32),
+        &state,
+    );
+    assert_eq!(2, analyser.state.pickups.len());
+    let kit = &analyser.state.pickups[&EntityId::from(10u32)];
+    assert_eq!(
+        (PickupKind::HealthKit, PickupSize::Small, false, true),
+        (kit.kind, kit.size, kit.dropped, kit.available)
+    );
+    let pack = &analyser.state.pickups[&EntityId::from(11u32)];
+    assert_eq!((PickupKind::AmmoPack, true), (pack.kind, pack.dropped));
+
+    analyser.handle_message(
+        &packet_entities(
+            vec![test_pickup_entity(
+                10,
+                0,
+                UpdateType::Preserve,
+                Some(EF_NODRAW),
+            )],
+            vec![],
+        ),
+        DemoTick::from(5u32),
+        &state,
+    );
+    analyser.handle_message(
+        &packet_entities(
+            vec![test_pickup_entity(10, 0, UpdateType::Preserve, Some(0))],
+            vec![11],
+        ),
+        DemoTick::from(9u32),
+        &state,
+    );
+    let kit = &analyser.state.pickups[&EntityId::from(10u32)];
+    assert!(kit.available);
+    assert_eq!(vec![DemoTick::from(5u32)], kit.taken);
+    assert_eq!(vec![DemoTick::from(9u32)], kit.respawned);
+    // dropped ammo packs are removed once picked up
+    assert!(!analyser.state.pickups.contains_key(&EntityId::from(11u32)));
+    assert_eq!(1, analyser.state.removed.len());
+    let pack = &analyser.state.removed[0];
+    assert_eq!(
+        (EntityId::from(11u32), Some(DemoTick::from(9u32))),
+        (pack.entity, pack.removed)
+    );
+
+    // re-entering the pvs keeps the history of the pickup
+    analyser.handle_message(
+        &packet_entities(
+            vec![test_pickup_entity(
+                10,
+                0,
+                UpdateType::Enter,
+                Some(EF_NODRAW),
+            )],
+            vec![],
+        ),
+        DemoTick::from(12u32),
+        &state,
+    );
+    let kit = &analyser.state.pickups[&EntityId::from(10u32)];
+    assert!(!kit.available);
+    assert_eq!(vec![DemoTick::from(5u32)], kit.taken);
+    assert_eq!(vec![DemoTick::from(9u32)], kit.respawned);
+}
+
+#[test]
+fn test_pickup_heal_matching() {
+    use crate::test_util::game_event;
+
+    let state = test_state();
+    let mut analyser = PickupAnalyser::new();
+    analyser
+        .user_ids
+        .insert(EntityId::from(3u32), UserId::from(30u16));
+    analyser
+        .user_ids
+        .insert(EntityId::from(4u32), UserId::from(40u16));
+
+    let pickup = |user_id: u16| {
+        game_event(GameEvent::ItemPickup(ItemPickupEvent {
+            user_id,
+            item: "item_healthkit_small".into(),
+        }))
+    };
+    let heal = |ent_index: u8, amount: u16| {
+        game_event(GameEvent::PlayerHealOnHit(PlayerHealOnHitEvent {
+            amount,
+            ent_index,
+            weapon_def_index: 0,
+        }))
+    };
+
+    // heal before the pickup
+    analyser.handle_message(&heal(3, 20), DemoTick::from(1u32), &state);
+    analyser.handle_message(&pickup(30), DemoTick::from(1u32), &state);
+    // heal after the pickup
+    analyser.handle_message(&pickup(40), DemoTick::from(2u32), &state);
+    analyser.handle_message(&heal(4, 35), DemoTick::from(2u32), &state);
+    // heals from an earlier tick aren't matched
+    analyser.handle_message(&heal(3, 50), DemoTick::from(3u32), &state);
+    analyser.handle_message(&pickup(30), DemoTick::from(4u32), &state);
+
+    assert_eq!(
+        vec![(30, 1, 20), (40, 2, 35), (30, 4, 0)],
+        analyser
+            .state
+            .events
+            .iter()
+            .map(|event| (u16::from(event.user), u32::from(event.tick), event.health))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        20,
+        analyser.state.players[&UserId::from(30u16)].health_gained
+    );
+    assert_eq!(
+        2,
+        analyser.state.players[&UserId::from(30u16)]
+            .health_kits
+            .small
+    );
+    assert_eq!(
+        35,
+        analyser.state.players[&UserId::from(40u16)].health_gained
+    );
+}
@@ -0,0 +1,470 @@
+use crate::demo::data::{DemoTick, UserInfo};
+use crate::demo::gameevent_gen::PlayerDeathEvent;
+use crate::demo::gamevent::GameEvent;
+use crate::demo::message::packetentities::{EntityId, PacketEntity};
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::stringtable::StringTableEntry;
+use crate::demo::parser::analyser::{Class, DeathFlag, KillType, Team, UserId};
+use crate::demo::parser::handler::MessageHandler;
+use crate::demo::sendprop::SendPropIdentifier;
+use crate::ParserState;
+use enumflags2::BitFlags;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+const TF_COND_DISGUISING: i64 = 1 << 2;
+const TF_COND_DISGUISED: i64 = 1 << 3;
+const TF_COND_STEALTHED: i64 = 1 << 4;
+
+/// The current disguise and cloak state of a player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SpyState {
+    pub entity: EntityId,
+    pub user: Option<UserId>,
+    pub disguising: bool,
+    pub disguised: bool,
+    pub disguise_team: Team,
+    pub disguise_class: Class,
+    /// Player the spy is disguised as
+    pub disguise_target: Option<UserId>,
+    pub cloaked: bool,
+    /// Cloak meter between 0 and 100
+    pub cloak_meter: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SpyEventKind {
+    Disguise {
+        team: Team,
+        class: Class,
+        target: Option<UserId>,
+    },
+    /// The disguise was removed, either by the spy or by attacking
+    Undisguise,
+    Cloak {
+        cloak_meter: f32,
+    },
+    Decloak {
+        cloak_meter: f32,
+    },
+    FeignDeath {
+        killer: UserId,
+    },
+    /// The spy killed a player with a backstab
+    Backstab {
+        victim: UserId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpyEvent {
+    pub tick: DemoTick,
+    pub entity: Option<EntityId>,
+    pub user: Option<UserId>,
+    #[serde(flatten)]
+    pub kind: SpyEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Spies {
+    /// The current state of all players that have disguised or cloaked
+    pub spies: BTreeMap<EntityId, SpyState>,
+    pub events: Vec<SpyEvent>,
+}
+
+/// Track spy disguises, cloaking, feign deaths and backstabs
+#[derive(Debug, Default)]
+pub struct SpyAnalyser {
+    state: Spies,
+    users: HashMap<EntityId, UserId>,
+}
+
+impl MessageHandler for SpyAnalyser {
+    type Output = Spies;
+
+    fn does_handle(message_type: MessageType) -> bool {
+        matches!(
+            message_type,
+            MessageType::PacketEntities | MessageType::GameEvent
+        )
+    }
+
+    fn handle_message(&mut self, message: &Message, tick: DemoTick, parser_state: &ParserState) {
+        match message {
+            Message::PacketEntities(message) => {
+                for entity in &message.entities {
+                    let is_player = parser_state
+                        .server_classes
+                        .get(usize::from(entity.server_class))
+                        .map(|class| class.name.as_str() == "CTFPlayer")
+                        .unwrap_or_default();
+                    if is_player {
+                        self.handle_player_entity(entity, tick, parser_state);
+                    }
+                }
+            }
+            Message::GameEvent(message) => {
+                if let GameEvent::PlayerDeath(death) = &message.event {
+                    self.handle_death(death, tick);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_string_entry(
+        &mut self,
+        table: &str,
+        index: usize,
+        entry: &StringTableEntry,
+        _parser_state: &ParserState,
+    ) {
+        if table == "userinfo" {
+            if let Ok(Some(user_info)) = UserInfo::parse_from_string_table(
+                index as u16,
+                entry.text.as_deref(),
+                entry.extra_data.as_ref().map(|data| data.data.clone()),
+            ) {
+                self.users
+                    .insert(user_info.entity_id, user_info.player_info.user_id);
+            }
+        }
+    }
+
+    fn into_output(self, _state: &ParserState) -> Self::Output {
+        self.state
+    }
+}
+
+impl SpyAnalyser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn handle_player_entity(
+        &mut self,
+        entity: &PacketEntity,
+        tick: DemoTick,
+        parser_state: &ParserState,
+    ) {
+        const PLAYER_COND: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerShared", "m_nPlayerCond");
+        const CONDITION_BITS: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerConditionListExclusive", "_condition_bits");
+        const DISGUISE_TEAM: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerShared", "m_nDisguiseTeam");
+        const DISGUISE_CLASS: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerShared", "m_nDisguiseClass");
+        const DISGUISE_TARGET: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerShared", "m_iDisguiseTargetIndex");
+        const CLOAK_METER: SendPropIdentifier =
+            SendPropIdentifier::new("DT_TFPlayerShared", "m_flCloakMeter");
+
+        let mut cond = None;
+        let mut disguise_team = None;
+        let mut disguise_class = None;
+        let mut disguise_target = None;
+        let mut cloak_meter = None;
+
+        for prop in entity.props(parser_state) {
+            match prop.identifier {
+                PLAYER_COND | CONDITION_BITS => cond = i64::try_from(&prop.value).ok(),
+                DISGUISE_TEAM => disguise_team = i64::try_from(&prop.value).ok().map(Team::new),
+                DISGUISE_CLASS => disguise_class = i64::try_from(&prop.value).ok().map(Class::new),
+                DISGUISE_TARGET => disguise_target = i64::try_from(&prop.value).ok(),
+                CLOAK_METER => cloak_meter = f32::try_from(&prop.value).ok(),
+                _ => {}
+            }
+        }
+
+        let entity_id = entity.entity_index;
+        if !self.state.spies.contains_key(&entity_id) {
+            // only start tracking players once they start using spy abilities
+            let uses_spy_abilities = cond
+                .map(|cond| cond & (TF_COND_DISGUISING | TF_COND_DISGUISED | TF_COND_STEALTHED))
+                .unwrap_or_default()
+                != 0;
+            if !uses_spy_abilities {
+                return;
+            }
+        }
+
+        let user = self.users.get(&entity_id).copied();
+        let target =
+            disguise_target.map(|target| self.users.get(&EntityId::from(target as u32)).copied());
+        let spy = self
+            .state
+            .spies
+            .entry(entity_id)
+            .or_insert_with(|| SpyState {
+                entity: entity_id,
+                ..SpyState::default()
+            });
+        spy.user = user;
+
+        if let Some(meter) = cloak_meter {
+            spy.cloak_meter = meter;
+        }
+        if let Some(team) = disguise_team {
+            spy.disguise_team = team;
+        }
+        if let Some(target) = target {
+            spy.disguise_target = target;
+        }
+
+        let mut events = Vec::new();
+
+        if let Some(class) = disguise_class {
+            if class != spy.disguise_class && class != Class::Other {
+                events.push(SpyEventKind::Disguise {
+                    team: spy.disguise_team,
+                    class,
+                    target: spy.disguise_target,
+                });
+            }
+            spy.disguise_class = class;
+        }
+
+        if let Some(cond) = cond {
+            let disguised = cond & TF_COND_DISGUISED != 0;
+            let cloaked = cond & TF_COND_STEALTHED != 0;
+
+            if spy.disguised && !disguised {
+                events.push(SpyEventKind::Undisguise);
+                spy.disguise_class = Class::Other;
+                spy.disguise_target = None;
+            }
+            if cloaked != spy.cloaked {
+                events.push(if cloaked {
+                    SpyEventKind::Cloak {
+                        cloak_meter: spy.cloak_meter,
+                    }
+                } else {
+                    SpyEventKind::Decloak {
+                        cloak_meter: spy.cloak_meter,
+                    }
+                });
+            }
+
+            spy.disguising = cond & TF_COND_DISGUISING != 0;
+            spy.disguised = disguised;
+            spy.cloaked = cloaked;
+        }
+
+        self.state
+            .events
+            .extend(events.into_iter().map(|kind| SpyEvent {
+                tick,
+                entity: Some(entity_id),
+                user,
+                kind,
+            }));
+    }
+
+    fn handle_death(&mut self, death: &PlayerDeathEvent, tick: DemoTick) {
+        if BitFlags::<DeathFlag>::from_bits_truncate(death.death_flags)
+            .contains(DeathFlag::FeignDeath)
+        {
+            let user = UserId::from(death.user_id);
+            self.state.events.push(SpyEvent {
+                tick,
+                entity: self.entity_for_user(user),
+                user: Some(user),
+                kind: SpyEventKind::FeignDeath {
+                    killer: UserId::from(death.attacker),
+                },
+            });
+        }
+        if KillType::new(death.custom_kill) == KillType::Backstab {
+            let user = UserId::from(death.attacker);
+            self.state.events.push(SpyEvent {
+                tick,
+                entity: self.entity_for_user(user),
+                user: Some(user),
+                kind: SpyEventKind::Backstab {
+                    victim: UserId::from(death.user_id),
+                },
+            });
+        }
+    }
+
+    fn entity_for_user(&self, user: UserId) -> Option<EntityId> {
+        self.users
+            .iter()
+            .find(|(_, user_id)| **user_id == user)
+            .map(|(entity, _)| *entity)
+    }
+}
+
+#[test]
+fn test_spy_deaths() {
+    use crate::test_util::death_event;
+
+    let mut analyser = SpyAnalyser::new();
+    analyser
+        .users
+        .insert(EntityId::from(3u32), UserId::from(30u16));
+    analyser
+        .users
+        .insert(EntityId::from(4u32), UserId::from(40u16));
+
+    // normal kill
+    analyser.handle_death(&death_event(30, 40, 0, 0), DemoTick::from(1u32));
+    assert!(analyser.state.events.is_empty());
+
+    analyser.handle_death(
+        &death_event(30, 40, DeathFlag::FeignDeath as u16, 0),
+        DemoTick::from(2u32),
+    );
+    analyser.handle_death(&death_event(40, 30, 0, 2), DemoTick::from(3u32));
+    assert_eq!(
+        vec![
+            SpyEvent {
+                tick: DemoTick::from(2u32),
+                entity: Some(EntityId::from(3u32)),
+                user: Some(UserId::from(30u16)),
+                kind: SpyEventKind::FeignDeath {
+                    killer: UserId::from(40u16)
+                },
+            },
+            SpyEvent {
+                tick: DemoTick::from(3u32),
+                entity: Some(EntityId::from(3u32)),
+                user: Some(UserId::from(30u16)),
+                kind: SpyEventKind::Backstab {
+                    victim: UserId::from(40u16)
+                },
+            },
+        ],
+        analyser.state.events
+    );
+}
+
+#[test]
+fn test_spy_disguise_cloak() {
+    use crate::demo::message::packetentities::UpdateType;
+    use crate::demo::sendprop::SendPropValue;
+    use crate::test_util::packet_entity;
+
+    let cond = SendPropIdentifier::new("DT_TFPlayerShared", "m_nPlayerCond");
+    let disguise_team = SendPropIdentifier::new("DT_TFPlayerShared", "m_nDisguiseTeam");
+    let disguise_class = SendPropIdentifier::new("DT_TFPlayerShared", "m_nDisguiseClass");
+    let disguise_target = SendPropIdentifier::new("DT_TFPlayerShared", "m_iDisguiseTargetIndex");
+    let cloak_meter = SendPropIdentifier::new("DT_TFPlayerShared", "m_flCloakMeter");
+
+    let state = ParserState::new(24, SpyAnalyser::does_handle, false);
+    let mut analyser = SpyAnalyser::new();
+    analyser
+        .users
+        .insert(EntityId::from(3u32), UserId::from(30u16));
+    analyser
+        .users
+        .insert(EntityId::from(5u32), UserId::from(50u16));
+
+    // players aren't tracked until they use spy abilities
+    analyser.handle_player_entity(
+        &packet_entity(
+            3,
+            0,
+            UpdateType::Preserve,
+            vec![
+                (cond, SendPropValue::Integer(0)),
+                (cloak_meter, SendPropValue::Float(100.0)),
+            ],
+        ),
+        DemoTick::from(1u32),
+        &state,
+    );
+    assert!(analyser.state.spies.is_empty());
+
+    analyser.handle_player_entity(
+        &packet_entity(
+            3,
+            0,
+            UpdateType::Preserve,
+            vec![
+                (cond, SendPropValue::Integer(TF_COND_DISGUISED)),
+                (disguise_team, SendPropValue::Integer(3)),
+                (disguise_class, SendPropValue::Integer(6)),
+                (disguise_target, SendPropValue::Integer(5)),
+            ],
+        ),
+        DemoTick::from(2u32),
+        &state,
+    );
+    analyser.handle_player_entity(
+        &packet_entity(
+            3,
+            0,
+            UpdateType::Preserve,
+            vec![
+                (
+                    cond,
+                    SendPropValue::Integer(TF_COND_DISGUISED | TF_COND_STEALTHED),
+                ),
+                (cloak_meter, SendPropValue::Float(80.0)),
+            ],
+        ),
+        DemoTick::from(3u32),
+        &state,
+    );
+    assert_eq!(
+        SpyState {
+            entity: EntityId::from(3u32),
+            user: Some(UserId::from(30u16)),
+            disguising: false,
+            disguised: true,
+            disguise_team: Team::Blue,
+            disguise_class: Class::Heavy,
+            disguise_target: Some(UserId::from(50u16)),
+            cloaked: true,
+            cloak_meter: 80.0,
+        },
+        analyser.state.spies[&EntityId::from(3u32)]
+    );
+
+    analyser.handle_player_entity(
+        &packet_entity(
+            3,
+            0,
+            UpdateType::Preserve,
+            vec![
+                (cond, SendPropValue::Integer(0)),
+                (cloak_meter, SendPropValue::Float(40.0)),
+            ],
+        ),
+        DemoTick::from(4u32),
+        &state,
+    );
+    let spy = &analyser.state.spies[&EntityId::from(3u32)];
+    assert!(!spy.disguised && !spy.cloaked);
+    assert_eq!(Class::Other, spy.disguise_class);
+    assert_eq!(None, spy.disguise_target);
+
+    let events: Vec<_> = analyser
+        .state
+        .events
+        .iter()
+        .map(|event| (u32::from(event.tick), event.kind.clone()))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                2,
+                SpyEventKind::Disguise {
+                    team: Team::Blue,
+                    class: Class::Heavy,
+                    target: Some(UserId::from(50u16)),
+                }
+            ),
+            (3, SpyEventKind::Cloak { cloak_meter: 80.0 }),
+            (4, SpyEventKind::Undisguise),
+            (4, SpyEventKind::Decloak { cloak_meter: 40.0 }),
+        ],
+        events
+    );
+}
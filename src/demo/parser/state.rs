@@ -12,9 +12,10 @@ use crate::demo::message::{Message, MessageType};
 use crate::demo::packet::datatable::{
     ClassId, ParseSendTable, SendTable, SendTableName, ServerClass,
 };
-use crate::demo::packet::stringtable::StringTableEntry;
+use crate::demo::packet::stringtable::{StringTable, StringTableEntry};
 
 use crate::demo::data::DemoTick;
+use crate::demo::parser::stringtablestore::StringTableStore;
 use crate::demo::sendprop::{SendProp, SendPropIdentifier};
 use crate::nullhasher::NullHasherBuilder;
 use crate::{Result, Stream};
@@ -42,9 +43,15 @@ pub struct ParserState {
     pub server_classes: Vec<ServerClass>,
     pub instance_baselines: [Baseline; 2],
     pub demo_meta: DemoMeta,
+    /// Current contents of the string tables
+    ///
+    /// The entries of all tables are only stored when parsing all messages,
+    /// otherwise only the model and sound precache tables are stored.
+    pub string_table_store: StringTableStore,
     analyser_handles: fn(message_type: MessageType) -> bool,
     handle_entities: bool,
     parse_all: bool,
+    store_string_tables: bool,
     pub protocol_version: u32,
 }
 
@@ -87,9 +94,11 @@ impl<'a> ParserState {
             server_classes: Vec::new(),
             instance_baselines: [Baseline::default(), Baseline::default()],
             demo_meta: DemoMeta::default(),
+            string_table_store: StringTableStore::new(),
             analyser_handles,
             handle_entities: analyser_handles(MessageType::PacketEntities) || parse_all,
             parse_all,
+            store_string_tables: parse_all,
             protocol_version,
         }
     }
@@ -201,6 +210,11 @@ impl<'a> ParserState {
         self.string_tables.push(table);
     }
 
+    pub fn handle_string_table(&mut self, table: &StringTable) {
+        self.handle_string_table_meta(table.get_table_meta());
+        self.string_table_store.create_table(table);
+    }
+
     pub fn should_parse_message(&self, message_type: MessageType) -> bool {
         self.parse_all
             || if message_type == MessageType::PacketEntities {
@@ -278,12 +292,11 @@ impl<'a> ParserState {
         }
     }
 
-    pub fn handle_string_entry(
-        &mut self,
-        table: &str,
-        _index: usize,
-        entry: &StringTableEntry<'a>,
-    ) {
+    pub fn handle_string_entry(&mut self, table: &str, index: usize, entry: &StringTableEntry<'a>) {
+        // the precache tables are always needed for the model and sound names
+        if self.store_string_tables || matches!(table, "modelprecache" | "soundprecache") {
+            self.string_table_store.set_entry(table, index, entry);
+        }
         if table == "instancebaseline" {
             if let (Some(extra), Ok(class_id)) = (&entry.extra_data, entry.text().parse()) {
                 let baseline = StaticBaseline::new(class_id, extra.data.to_owned());
@@ -293,6 +306,16 @@ impl<'a> ParserState {
         }
     }
 
+    pub fn sound_name(&self, index: u16) -> Option<&str> {
+        self.string_table_store
+            .text("soundprecache", index as usize)
+    }
+
+    pub fn model_name(&self, index: u32) -> Option<&str> {
+        self.string_table_store
+            .text("modelprecache", index as usize)
+    }
+
     pub fn index_for_prop(&self, class: ClassId, prop: SendPropIdentifier) -> Option<u32> {
         let send_table = self.send_tables.get(usize::from(class))?;
         send_table
@@ -406,3 +429,35 @@ impl From<BaselineEntity> for PacketEntity {
         }
     }
 }
+
+#[test]
+fn test_string_table_store_only_when_parsing_all() {
+    let table = |name: &str| StringTable {
+        name: name.to_string().into(),
+        entries: vec![],
+        max_entries: 1024,
+        fixed_user_data_size: None,
+        client_entries: None,
+        compressed: false,
+    };
+    let entry = StringTableEntry {
+        text: Some("entry".into()),
+        extra_data: None,
+    };
+
+    for parse_all in [false, true] {
+        let mut state = ParserState::new(24, |_| false, parse_all);
+        state.handle_string_table(&table("modelprecache"));
+        state.handle_string_table(&table("downloadables"));
+        state.handle_string_entry("modelprecache", 1, &entry);
+        state.handle_string_entry("downloadables", 1, &entry);
+
+        // the ids of all tables are known
+        assert_eq!(Some(1), state.string_table_store.id("downloadables"));
+        assert_eq!(Some("entry"), state.model_name(1));
+        assert_eq!(
+            parse_all,
+            state.string_table_store.text("downloadables", 1).is_some()
+        );
+    }
+}
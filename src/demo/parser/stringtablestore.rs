@@ -0,0 +1,226 @@
+use crate::demo::data::DemoTick;
+use crate::demo::message::stringtable::StringTableMeta;
+use crate::demo::packet::stringtable::{
+    FixedUserDataSize, StringTable, StringTableEntry, StringTablePacket,
+};
+use std::borrow::Cow;
+
+/// The current contents of a string table
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredStringTable {
+    pub name: String,
+    pub max_entries: u16,
+    pub fixed_user_data_size: Option<FixedUserDataSize>,
+    /// Entries indexed by their entry index
+    pub entries: Vec<StringTableEntry<'static>>,
+    pub client_entries: Option<Vec<StringTableEntry<'static>>>,
+}
+
+impl StoredStringTable {
+    pub fn get(&self, index: usize) -> Option<&StringTableEntry<'static>> {
+        self.entries.get(index)
+    }
+
+    /// Get the text of an entry, empty entries are ignored
+    pub fn text(&self, index: usize) -> Option<&str> {
+        self.get(index)
+            .map(StringTableEntry::text)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Find the index of the entry with the provided text
+    pub fn find(&self, text: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.text() == text)
+    }
+
+    pub fn meta(&self) -> StringTableMeta {
+        StringTableMeta {
+            max_entries: self.max_entries,
+            fixed_userdata_size: self.fixed_user_data_size,
+        }
+    }
+
+    fn set_entry(&mut self, index: usize, entry: &StringTableEntry) {
+        if self.entries.len() <= index {
+            self.entries.resize(index + 1, StringTableEntry::default());
+        }
+        let existing = &mut self.entries[index];
+        // updates only contain the parts of the entry that changed
+        if let Some(text) = entry.text.as_deref() {
+            existing.text = Some(Cow::Owned(text.into()));
+        }
+        if let Some(extra_data) = entry.extra_data.as_ref() {
+            existing.extra_data = Some(extra_data.to_owned());
+        }
+    }
+
+    pub fn to_string_table(&self) -> StringTable<'static> {
+        StringTable {
+            name: Cow::Owned(self.name.clone()),
+            entries: self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| (index as u16, entry.clone()))
+                .collect(),
+            max_entries: self.max_entries,
+            fixed_user_data_size: self.fixed_user_data_size,
+            client_entries: self.client_entries.clone(),
+            compressed: false,
+        }
+    }
+}
+
+/// Keeps track of the contents of all string tables as the demo is parsed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StringTableStore {
+    tables: Vec<StoredStringTable>,
+}
+
+impl StringTableStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a table without any entries, an existing table with the same name is cleared
+    pub fn create_table(&mut self, table: &StringTable) {
+        let stored = StoredStringTable {
+            name: table.name.to_string(),
+            max_entries: table.max_entries,
+            fixed_user_data_size: table.fixed_user_data_size,
+            entries: Vec::with_capacity(table.entries.len()),
+            client_entries: table
+                .client_entries
+                .as_ref()
+                .map(|entries| entries.iter().map(StringTableEntry::to_owned).collect()),
+        };
+        match self
+            .tables
+            .iter_mut()
+            .find(|existing| existing.name == stored.name)
+        {
+            Some(existing) => *existing = stored,
+            None => self.tables.push(stored),
+        }
+    }
+
+    pub fn set_entry(&mut self, table: &str, index: usize, entry: &StringTableEntry) {
+        if let Some(table) = self.tables.iter_mut().find(|stored| stored.name == table) {
+            table.set_entry(index, entry);
+        }
+    }
+
+    pub fn get(&self, table: &str) -> Option<&StoredStringTable> {
+        self.tables.iter().find(|stored| stored.name == table)
+    }
+
+    /// Get a table by the id used in string table updates
+    pub fn get_by_id(&self, table_id: u8) -> Option<&StoredStringTable> {
+        self.tables.get(table_id as usize)
+    }
+
+    /// Get the id used in string table updates for a table
+    pub fn id(&self, table: &str) -> Option<u8> {
+        self.tables
+            .iter()
+            .position(|stored| stored.name == table)
+            .map(|index| index as u8)
+    }
+
+    pub fn entry(&self, table: &str, index: usize) -> Option<&StringTableEntry<'static>> {
+        self.get(table)?.get(index)
+    }
+
+    /// Get the text of an entry, empty entries are ignored
+    pub fn text(&self, table: &str, index: usize) -> Option<&str> {
+        self.get(table)?.text(index)
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &StoredStringTable> {
+        self.tables.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Create a packet containing the full contents of all tables
+    pub fn snapshot(&self, tick: DemoTick) -> StringTablePacket<'static> {
+        StringTablePacket {
+            tick,
+            tables: self
+                .tables
+                .iter()
+                .map(StoredStringTable::to_string_table)
+                .collect(),
+        }
+    }
+}
+
+#[test]
+fn test_string_table_store() {
+    let mut store = StringTableStore::new();
+    let table = StringTable {
+        name: "modelprecache".into(),
+        entries: vec![],
+        max_entries: 1024,
+        fixed_user_data_size: None,
+        client_entries: None,
+        compressed: false,
+    };
+    store.create_table(&table);
+    store.set_entry(
+        "modelprecache",
+        1,
+        &StringTableEntry {
+            text: Some("maps/cp_badlands.bsp".into()),
+            extra_data: None,
+        },
+    );
+    store.set_entry(
+        "modelprecache",
+        2,
+        &StringTableEntry {
+            text: Some("models/weapons/w_rocket.mdl".into()),
+            extra_data: None,
+        },
+    );
+    // updates without text keep the existing text
+    store.set_entry("modelprecache", 2, &StringTableEntry::default());
+
+    assert_eq!(1, store.len());
+    assert_eq!(Some(0), store.id("modelprecache"));
+    assert_eq!(None, store.text("modelprecache", 0));
+    assert_eq!(
+        Some("models/weapons/w_rocket.mdl"),
+        store.text("modelprecache", 2)
+    );
+    assert_eq!(
+        Some(2),
+        store
+            .get("modelprecache")
+            .unwrap()
+            .find("models/weapons/w_rocket.mdl")
+    );
+    assert_eq!(None, store.text("soundprecache", 0));
+
+    let snapshot = store.snapshot(DemoTick::from(10));
+    assert_eq!(1, snapshot.tables.len());
+    assert_eq!(3, snapshot.tables[0].entries.len());
+    assert_eq!(
+        (1, "maps/cp_badlands.bsp"),
+        (
+            snapshot.tables[0].entries[1].0,
+            snapshot.tables[0].entries[1].1.text()
+        )
+    );
+
+    // recreating a table clears it
+    store.create_table(&table);
+    assert_eq!(1, store.len());
+    assert_eq!(None, store.text("modelprecache", 1));
+}
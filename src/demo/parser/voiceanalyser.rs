@@ -0,0 +1,185 @@
+use crate::demo::data::{DemoTick, UserInfo};
+use crate::demo::message::packetentities::EntityId;
+use crate::demo::message::voice::VoiceDataMessage;
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::stringtable::StringTableEntry;
+use crate::demo::parser::analyser::UserId;
+use crate::demo::parser::handler::MessageHandler;
+use crate::demo::voice::{OggOpusWriter, SteamVoiceData, SteamVoicePayload, OPUS_SAMPLE_RATE};
+use crate::{ParserState, ReadResult};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Sample rate used by the steam voice codec if the packets don't specify one
+const STEAM_VOICE_SAMPLE_RATE: u16 = 24000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoicePacket {
+    pub tick: DemoTick,
+    pub proximity: bool,
+    pub data: Vec<u8>,
+}
+
+impl VoicePacket {
+    pub fn steam_voice(&self) -> ReadResult<SteamVoiceData> {
+        SteamVoiceData::parse(&self.data)
+    }
+}
+
+/// All voice packets send by a single client
+#[derive(Debug, Clone, PartialEq)]
+pub struct Speaker {
+    pub entity_id: EntityId,
+    pub user_id: Option<UserId>,
+    pub name: Option<String>,
+    pub packets: Vec<VoicePacket>,
+}
+
+impl Speaker {
+    /// Write the speaker's audio as ogg opus, silence is inserted between packets to keep the audio aligned to the demo
+    ///
+    /// Only works for demos using the steam voice codec, packets that can't be decoded are skipped
+    pub fn write_ogg_opus<W: Write>(&self, writer: W, interval_per_tick: f32) -> io::Result<W> {
+        let mut sample_rate = STEAM_VOICE_SAMPLE_RATE;
+        let mut ogg = OggOpusWriter::new(writer, sample_rate as u32)?;
+
+        for packet in &self.packets {
+            let start = u32::from(packet.tick) as f64 * interval_per_tick as f64;
+            ogg.pad_to((start * OPUS_SAMPLE_RATE as f64) as u64)?;
+
+            let voice = match packet.steam_voice() {
+                Ok(voice) => voice,
+                Err(_) => continue,
+            };
+            for payload in &voice.payloads {
+                match payload {
+                    SteamVoicePayload::SampleRate(rate) if *rate > 0 => sample_rate = *rate,
+                    SteamVoicePayload::Silence(samples) => {
+                        let silence =
+                            *samples as u64 * OPUS_SAMPLE_RATE as u64 / sample_rate as u64;
+                        ogg.pad_to(ogg.granule_position() + silence)?;
+                    }
+                    _ => {}
+                }
+            }
+            for frame in voice.opus_frames() {
+                ogg.write_packet(frame)?;
+            }
+        }
+
+        ogg.finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VoiceRecording {
+    pub codec: String,
+    pub quality: u8,
+    pub sample_rate: u16,
+    pub interval_per_tick: f32,
+    pub speakers: Vec<Speaker>,
+}
+
+impl VoiceRecording {
+    /// Whether the voice data uses the steam codec which contains opus frames
+    pub fn is_steam_voice(&self) -> bool {
+        self.codec == "steam"
+    }
+}
+
+/// Collect all voice data in the demo, grouped by speaker
+#[derive(Debug, Default)]
+pub struct VoiceAnalyser {
+    recording: VoiceRecording,
+    users: HashMap<EntityId, (UserId, String)>,
+    speakers: HashMap<(EntityId, Option<UserId>), usize>,
+}
+
+impl MessageHandler for VoiceAnalyser {
+    type Output = VoiceRecording;
+
+    fn does_handle(message_type: MessageType) -> bool {
+        matches!(
+            message_type,
+            MessageType::VoiceInit | MessageType::VoiceData | MessageType::ServerInfo
+        )
+    }
+
+    fn handle_message(&mut self, message: &Message, tick: DemoTick, _parser_state: &ParserState) {
+        match message {
+            Message::ServerInfo(message) => {
+                self.recording.interval_per_tick = message.interval_per_tick
+            }
+            Message::VoiceInit(message) => {
+                self.recording.codec = message.codec.clone();
+                self.recording.quality = message.quality;
+                self.recording.sample_rate = message.sampling_rate;
+            }
+            Message::VoiceData(message) => self.handle_voice_data(message, tick),
+            _ => {}
+        }
+    }
+
+    fn handle_string_entry(
+        &mut self,
+        table: &str,
+        index: usize,
+        entry: &StringTableEntry,
+        _parser_state: &ParserState,
+    ) {
+        if table == "userinfo" {
+            if let Ok(Some(user_info)) = UserInfo::parse_from_string_table(
+                index as u16,
+                entry.text.as_deref(),
+                entry.extra_data.as_ref().map(|data| data.data.clone()),
+            ) {
+                self.users.insert(
+                    user_info.entity_id,
+                    (user_info.player_info.user_id, user_info.player_info.name),
+                );
+            }
+        }
+    }
+
+    fn into_output(self, _state: &ParserState) -> Self::Output {
+        self.recording
+    }
+}
+
+impl VoiceAnalyser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn handle_voice_data(&mut self, message: &VoiceDataMessage, tick: DemoTick) {
+        let mut stream = message.data.clone();
+        let data = match stream.read_bytes(stream.bit_len() / 8) {
+            Ok(data) => data.into_owned(),
+            Err(_) => return,
+        };
+
+        let entity_id = EntityId::from(message.client as u32 + 1);
+        let user = self.users.get(&entity_id);
+        let user_id = user.map(|(user_id, _)| *user_id);
+
+        let speakers = &mut self.recording.speakers;
+        let index = *self
+            .speakers
+            .entry((entity_id, user_id))
+            .or_insert_with(|| {
+                speakers.push(Speaker {
+                    entity_id,
+                    user_id,
+                    name: user.map(|(_, name)| name.clone()),
+                    packets: Vec::new(),
+                });
+                speakers.len() - 1
+            });
+
+        speakers[index].packets.push(VoicePacket {
+            tick,
+            proximity: message.proximity > 0,
+            data,
+        });
+    }
+}
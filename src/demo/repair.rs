@@ -0,0 +1,137 @@
+//! Repairing demos that weren't finished properly, like the demos of a crashed client
+
+use crate::demo::data::DemoTick;
+use crate::demo::header::Header;
+use crate::demo::packet::stop::StopPacket;
+use crate::demo::packet::Packet;
+use crate::demo::parser::{DemoHandler, Encode, RawPacketStream};
+use crate::{Demo, ParseError, Result};
+use bitbuffer::{BitRead, BitWrite, BitWriteStream, LittleEndian};
+use std::fmt::{self, Display, Formatter};
+
+/// The changes made while repairing a demo
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    /// The headers before and after the repair, if they differ
+    pub header: Option<Box<[Header; 2]>>,
+    /// The number of bytes cut off from the end of the demo
+    pub truncated_bytes: usize,
+    /// The error encountered while reading or handling the cut off data, `None` if the data was only incomplete
+    pub error: Option<ParseError>,
+    /// Whether the demo was missing a stop packet
+    pub stop_added: bool,
+}
+
+impl RepairReport {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_none() && self.truncated_bytes == 0 && !self.stop_added
+    }
+}
+
+impl Display for RepairReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "demo is intact");
+        }
+        if let Some(headers) = &self.header {
+            let [old, new] = headers.as_ref();
+            writeln!(f, "ticks: {} -> {}", old.ticks, new.ticks)?;
+            writeln!(f, "frames: {} -> {}", old.frames, new.frames)?;
+            writeln!(f, "duration: {:.2}s -> {:.2}s", old.duration, new.duration)?;
+        }
+        if self.truncated_bytes > 0 {
+            match &self.error {
+                Some(e) => writeln!(f, "removed {} corrupt bytes: {}", self.truncated_bytes, e)?,
+                None => writeln!(f, "removed {} trailing bytes", self.truncated_bytes)?,
+            }
+        }
+        if self.stop_added {
+            writeln!(f, "added missing stop packet")?;
+        }
+        Ok(())
+    }
+}
+
+/// Repair a truncated or corrupted demo
+///
+/// The packets are copied unchanged up to the last complete packet that can be read,
+/// any trailing data after it is cut off and a stop packet is added if missing.
+/// If the `ticks` or `frames` of the header don't match the packets, they are recalculated
+/// together with the `duration`, using the tick interval from the server info.
+/// When the demo has no server info, the tick interval of the original header is used instead,
+/// or 66 ticks per second if the header has no duration.
+pub fn repair_demo(input: &[u8]) -> Result<(Vec<u8>, RepairReport)> {
+    let demo = Demo::new(input);
+    let mut stream = demo.get_stream();
+    let header = Header::read(&mut stream)?;
+    let start = stream.pos() / 8;
+    let mut packets = RawPacketStream::new(stream);
+
+    let mut handler = DemoHandler::default();
+    handler.handle_header(&header);
+
+    let mut report = RepairReport::default();
+    let mut end = start;
+    let mut last_tick = DemoTick::default();
+    let mut frames = 0;
+    let mut stopped = false;
+    loop {
+        let packet = match packets.next(&handler.state_handler) {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
+            Err(e) => {
+                report.error = Some(e);
+                break;
+            }
+        };
+        let tick = packet.tick();
+        let is_frame = matches!(packet, Packet::Message(_));
+        stopped = matches!(packet, Packet::Stop(_));
+
+        // a packet that can't be handled is treated as corrupt and cut off with everything after it
+        if let Err(e) = handler.handle_packet(packet) {
+            report.error = Some(e);
+            stopped = false;
+            break;
+        }
+        end = packets.pos().div_ceil(8);
+        last_tick = tick;
+        if is_frame {
+            frames += 1;
+        }
+    }
+    report.truncated_bytes = input.len() - end;
+
+    let mut repaired_header = header.clone();
+    let ticks = u32::from(last_tick);
+    if header.ticks != ticks || header.frames != frames {
+        repaired_header.ticks = ticks;
+        repaired_header.frames = frames;
+        let interval_per_tick = match handler.get_parser_state().demo_meta.interval_per_tick {
+            interval if interval > 0.0 => interval,
+            // fallback for demos cut off before the server info
+            _ if header.ticks > 0 && header.duration > 0.0 => header.duration / header.ticks as f32,
+            _ => 1.0 / 66.0,
+        };
+        repaired_header.duration = ticks as f32 * interval_per_tick;
+        report.header = Some(Box::new([header, repaired_header.clone()]));
+    }
+
+    let mut out = Vec::with_capacity(end + 8);
+    {
+        let mut stream = BitWriteStream::new(&mut out, LittleEndian);
+        repaired_header.write(&mut stream)?;
+    }
+    out.extend_from_slice(&input[start..end]);
+
+    if !stopped {
+        report.stop_added = true;
+        let mut stop = Vec::new();
+        let mut stream = BitWriteStream::new(&mut stop, LittleEndian);
+        Packet::Stop(StopPacket { tick: last_tick })
+            .encode(&mut stream, handler.get_parser_state())?;
+        out.extend_from_slice(&stop);
+    }
+
+    Ok((out, report))
+}
@@ -657,6 +657,11 @@ fn float_scale(bit_count: u8) -> f32 {
     (1i32.wrapping_shl(bit_count as u32)) as f32 - 1.0
 }
 
+fn scaled_float(raw: u32, bit_count: u8, low: f32, high: f32) -> f32 {
+    let percentage = (raw as f32) / float_scale(bit_count);
+    low + ((high - low) * percentage)
+}
+
 impl SendPropValue {
     pub fn parse(stream: &mut Stream, definition: &SendPropParseDefinition) -> Result<Self> {
         match definition {
@@ -807,9 +812,7 @@ impl SendPropValue {
                 high,
             } => {
                 let raw: u32 = stream.read_int(*bit_count as usize)?;
-                let scale = float_scale(*bit_count);
-                let percentage = (raw as f32) / scale;
-                Ok(low + ((high - low) * percentage))
+                Ok(scaled_float(raw, *bit_count, *low, *high))
             }
         }
     }
@@ -842,6 +845,12 @@ impl SendPropValue {
                 let percentage = (val - low) / (high - low);
                 let scale = float_scale(*bit_count);
                 let raw = (percentage * scale).round() as u32;
+                // the rounding errors can put us one step off from the value that was read,
+                // prefer the neighbour that decodes to the exact same float
+                let raw = [raw, raw.saturating_sub(1), raw.saturating_add(1)]
+                    .into_iter()
+                    .find(|raw| scaled_float(*raw, *bit_count, *low, *high) == val)
+                    .unwrap_or(raw);
                 raw.write_sized(stream, *bit_count as usize)?;
 
                 Ok(())
@@ -996,6 +1005,54 @@ fn test_send_prop_value_roundtrip() {
     );
 }
 
+#[test]
+fn test_scaled_float_bit_identical() {
+    use bitbuffer::{BitReadBuffer, BitReadStream};
+
+    let ranges = [
+        (0.0, 1.0),
+        (-1.0, 1.0),
+        (0.0, 360.0),
+        (-180.0, 180.0),
+        (0.0, 100.0),
+        (-4096.0, 4096.0),
+        (0.1, 102.3),
+        (-0.1, 2048.5),
+    ];
+    // with more bits, multiple encoded values can read as the same float
+    for bit_count in 1..=23u8 {
+        for (low, high) in ranges {
+            let definition = FloatDefinition::Scaled {
+                bit_count,
+                high,
+                low,
+            };
+            let max = (1u32 << bit_count) - 1;
+            let step = (max / 4096).max(1);
+            for raw in (0..=max).step_by(step as usize).chain([max]) {
+                let mut data = Vec::new();
+                {
+                    let mut write = BitWriteStream::new(&mut data, LittleEndian);
+                    raw.write_sized(&mut write, bit_count as usize).unwrap();
+                }
+                let mut read = BitReadStream::new(BitReadBuffer::new(&data, LittleEndian));
+                let value = SendPropValue::read_float(&mut read, &definition).unwrap();
+
+                let mut encoded = Vec::new();
+                {
+                    let mut write = BitWriteStream::new(&mut encoded, LittleEndian);
+                    SendPropValue::write_float(value, &mut write, &definition).unwrap();
+                }
+                assert_eq!(
+                    data, encoded,
+                    "{} bits between {} and {}: {} encoded as {:?}",
+                    bit_count, low, high, raw, encoded
+                );
+            }
+        }
+    }
+}
+
 impl From<i32> for SendPropValue {
     fn from(value: i32) -> Self {
         SendPropValue::Integer(value as i64)
@@ -1320,6 +1377,38 @@ pub fn write_bit_coord(val: f32, stream: &mut BitWriteStream<LittleEndian>) -> R
     Ok(())
 }
 
+/// Read a vector as written by `WriteBitVec3Coord`, components that are zero are omitted
+pub fn read_bit_vec3_coord(stream: &mut Stream) -> ReadResult<Vector> {
+    let (has_x, has_y, has_z) = stream.read()?;
+
+    Ok(Vector {
+        x: if has_x { read_bit_coord(stream)? } else { 0f32 },
+        y: if has_y { read_bit_coord(stream)? } else { 0f32 },
+        z: if has_z { read_bit_coord(stream)? } else { 0f32 },
+    })
+}
+
+pub fn write_bit_vec3_coord(
+    val: Vector,
+    stream: &mut BitWriteStream<LittleEndian>,
+) -> ReadResult<()> {
+    let has_x = val.x != 0.0;
+    let has_y = val.y != 0.0;
+    let has_z = val.z != 0.0;
+    (has_x, has_y, has_z).write(stream)?;
+
+    if has_x {
+        write_bit_coord(val.x, stream)?;
+    }
+    if has_y {
+        write_bit_coord(val.y, stream)?;
+    }
+    if has_z {
+        write_bit_coord(val.z, stream)?;
+    }
+    Ok(())
+}
+
 #[test]
 fn bit_coord_roundtrip() {
     use bitbuffer::BitReadBuffer;
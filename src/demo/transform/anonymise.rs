@@ -0,0 +1,259 @@
+use crate::demo::data::{MaybeUtf8String, SteamId, UserInfo};
+use crate::demo::gameevent_gen::GameEvent;
+use crate::demo::header::Header;
+use crate::demo::message::packetentities::EntityId;
+use crate::demo::message::usermessage::{ChatMessageKind, UserMessage};
+use crate::demo::message::Message;
+use crate::demo::packet::stringtable::StringTableEntry;
+use crate::demo::packet::Packet;
+use crate::demo::parser::analyser::UserId;
+use crate::demo::transform::Transform;
+use crate::ParserState;
+use std::collections::HashMap;
+use steamid_ng::SteamID;
+
+/// Who a pseudonym belongs to, players without a valid steam id are identified by their user id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Identity {
+    Steam(SteamID),
+    User(UserId),
+}
+
+impl Identity {
+    fn new(steam_id: &SteamId, user_id: UserId) -> Self {
+        match steam_id {
+            SteamId::User(steam_id) => Identity::Steam(*steam_id),
+            _ => Identity::User(user_id),
+        }
+    }
+}
+
+/// The replacement name and steam id for a player
+#[derive(Debug, Clone)]
+struct Pseudonym {
+    name: String,
+    /// Replacement steam id, players without a valid steam id keep their original
+    steam_id: Option<SteamId>,
+}
+
+/// Replace the names and steam ids of all players with pseudonyms
+///
+/// Every player gets the same `Player N` pseudonym and `[U:1:N]` steam id for the entire demo,
+/// based on their steam id.
+/// The friends id and custom files (sprays) in the user info are replaced or removed as well.
+/// The nick and server name in the header and the server name from the server info are removed.
+#[derive(Debug, Default)]
+pub struct Anonymise {
+    pseudonyms: HashMap<Identity, Pseudonym>,
+    users: HashMap<UserId, Identity>,
+    entities: HashMap<EntityId, Identity>,
+    /// Original names of the players, for messages that only contain the name
+    names: HashMap<String, String>,
+}
+
+impl Anonymise {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pseudonym(&mut self, identity: Identity, name: &str) -> Pseudonym {
+        let number = self.pseudonyms.len() as u32 + 1;
+        let pseudonym = self
+            .pseudonyms
+            .entry(identity)
+            .or_insert_with(|| Pseudonym {
+                name: format!("Player {}", number),
+                steam_id: match identity {
+                    Identity::Steam(mut steam_id) => {
+                        steam_id.set_account_id(number);
+                        Some(SteamId::User(steam_id))
+                    }
+                    Identity::User(_) => None,
+                },
+            })
+            .clone();
+        if !name.is_empty() {
+            self.names.insert(name.to_string(), pseudonym.name.clone());
+        }
+        pseudonym
+    }
+
+    fn user_pseudonym(
+        &mut self,
+        user_id: UserId,
+        network_id: Option<&str>,
+        name: &str,
+    ) -> Pseudonym {
+        let identity = match network_id.and_then(|network_id| network_id.parse().ok()) {
+            Some(steam_id) => Identity::new(&steam_id, user_id),
+            None => self
+                .users
+                .get(&user_id)
+                .copied()
+                .unwrap_or(Identity::User(user_id)),
+        };
+        self.users.insert(user_id, identity);
+        self.pseudonym(identity, name)
+    }
+
+    fn replace_name(&self, name: &mut MaybeUtf8String) {
+        if let Some(pseudonym) = self
+            .names
+            .get(String::from_utf8_lossy(name.as_bytes()).as_ref())
+        {
+            *name = pseudonym.as_str().into();
+        }
+    }
+
+    fn anonymise_user_info(&mut self, index: u16, entry: &mut StringTableEntry) {
+        let data = entry.extra_data.as_ref().map(|extra| extra.data.clone());
+        let mut info = match UserInfo::parse_from_string_table(index, entry.text.as_deref(), data) {
+            Ok(Some(info)) => info,
+            _ => return,
+        };
+
+        let identity = Identity::new(&info.player_info.steam_id, info.player_info.user_id);
+        self.users.insert(info.player_info.user_id, identity);
+        self.entities.insert(info.entity_id, identity);
+        let name = std::mem::take(&mut info.player_info.name);
+        let pseudonym = self.pseudonym(identity, &name);
+        info.player_info.name = pseudonym.name;
+        // the friends id is the account id of the player
+        info.player_info.friends_id = pseudonym
+            .steam_id
+            .as_ref()
+            .and_then(SteamId::account_id)
+            .unwrap_or_default();
+        if let Some(steam_id) = pseudonym.steam_id {
+            info.player_info.steam_id = steam_id;
+        }
+        // the crc's of the players spray
+        info.player_info.custom_file = [0; 4];
+
+        if let Ok(encoded) = info.encode_to_string_table() {
+            entry.extra_data = encoded.extra_data;
+        }
+    }
+
+    /// Replace the name and steam id in a player event
+    fn anonymise_player(
+        &mut self,
+        user_id: u16,
+        network_id: &mut MaybeUtf8String,
+        name: &mut MaybeUtf8String,
+    ) {
+        let pseudonym =
+            self.user_pseudonym(user_id.into(), Some(network_id.as_ref()), name.as_ref());
+        *name = pseudonym.name.as_str().into();
+        if let Some(steam_id) = pseudonym.steam_id {
+            *network_id = steam_id.to_raw(network_id.as_ref()).as_str().into();
+        }
+    }
+
+    fn anonymise_event(&mut self, event: &mut GameEvent) {
+        match event {
+            GameEvent::PlayerConnect(event) => {
+                self.anonymise_player(event.user_id, &mut event.network_id, &mut event.name);
+                event.address = MaybeUtf8String::default();
+            }
+            GameEvent::PlayerConnectClient(event) => {
+                self.anonymise_player(event.user_id, &mut event.network_id, &mut event.name);
+            }
+            GameEvent::PlayerInfo(event) => {
+                self.anonymise_player(event.user_id, &mut event.network_id, &mut event.name);
+            }
+            GameEvent::PlayerDisconnect(event) => {
+                self.anonymise_player(event.user_id, &mut event.network_id, &mut event.name);
+            }
+            GameEvent::PlayerChangeName(event) => {
+                let user_id = event.user_id.into();
+                self.user_pseudonym(user_id, None, event.old_name.as_ref());
+                let pseudonym = self.user_pseudonym(user_id, None, event.new_name.as_ref());
+                event.old_name = pseudonym.name.as_str().into();
+                event.new_name = pseudonym.name.as_str().into();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Transform for Anonymise {
+    fn transform_header(&mut self, header: &mut Header) {
+        header.nick = String::new();
+        header.server = String::new();
+    }
+
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        match packet {
+            Packet::Signon(message_packet) | Packet::Message(message_packet) => {
+                message_packet
+                    .messages
+                    .retain_mut(|message| self.transform_message(message, state));
+            }
+            Packet::StringTables(string_tables) => {
+                for table in string_tables
+                    .tables
+                    .iter_mut()
+                    .filter(|table| table.name == "userinfo")
+                {
+                    for (index, entry) in table.entries.iter_mut() {
+                        self.anonymise_user_info(*index, entry);
+                    }
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn transform_message(&mut self, message: &mut Message, state: &ParserState) -> bool {
+        match message {
+            Message::ServerInfo(info) => {
+                info.server_name = String::new();
+            }
+            Message::CreateStringTable(create) if create.table.name == "userinfo" => {
+                for (index, entry) in create.table.entries.iter_mut() {
+                    self.anonymise_user_info(*index, entry);
+                }
+            }
+            Message::UpdateStringTable(update) => {
+                let is_user_info = state
+                    .string_table_store
+                    .get_by_id(update.table_id)
+                    .map(|table| table.name == "userinfo")
+                    .unwrap_or_default();
+                if is_user_info {
+                    for (index, entry) in update.entries.iter_mut() {
+                        self.anonymise_user_info(*index, entry);
+                    }
+                }
+            }
+            Message::UserMessage(UserMessage::SayText2(chat)) => {
+                let pseudonym = self
+                    .entities
+                    .get(&chat.client)
+                    .and_then(|identity| self.pseudonyms.get(identity))
+                    .map(|pseudonym| &pseudonym.name);
+                match (pseudonym, &mut chat.from) {
+                    (Some(pseudonym), Some(from)) => *from = pseudonym.as_str().into(),
+                    (None, Some(from)) => self.replace_name(from),
+                    _ => {}
+                }
+                if chat.kind == ChatMessageKind::NameChange {
+                    match pseudonym {
+                        Some(pseudonym) => chat.text = pseudonym.as_str().into(),
+                        None => self.replace_name(&mut chat.text),
+                    }
+                }
+            }
+            Message::UserMessage(UserMessage::Text(text)) => {
+                for substitute in text.substitute.iter_mut() {
+                    self.replace_name(substitute);
+                }
+            }
+            Message::GameEvent(event) => self.anonymise_event(&mut event.event),
+            _ => {}
+        }
+        true
+    }
+}
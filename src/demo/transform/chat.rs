@@ -0,0 +1,19 @@
+use crate::demo::message::usermessage::{ChatMessageKind, UserMessage};
+use crate::demo::message::Message;
+use crate::demo::transform::Transform;
+use crate::ParserState;
+
+/// Remove all chat messages sent by players
+///
+/// Name change notifications are kept
+pub struct RemoveChat;
+
+impl Transform for RemoveChat {
+    fn transform_message(&mut self, message: &mut Message, _state: &ParserState) -> bool {
+        !matches!(
+            message,
+            Message::UserMessage(UserMessage::SayText2(chat))
+                if !matches!(chat.kind, ChatMessageKind::NameChange | ChatMessageKind::Empty)
+        )
+    }
+}
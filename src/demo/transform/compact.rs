@@ -0,0 +1,132 @@
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::{Packet, PacketType};
+use crate::demo::parser::Encode;
+use crate::demo::transform::Transform;
+use crate::ParserState;
+use bitbuffer::{BitWrite, BitWriteStream, LittleEndian};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Message types that are only needed for playing back the demo
+const REMOVED_MESSAGES: &[MessageType] = &[
+    MessageType::VoiceInit,
+    MessageType::VoiceData,
+    MessageType::ParseSounds,
+    MessageType::PreFetch,
+    MessageType::BspDecal,
+];
+
+/// Message types that are mostly not needed for analysis, but can contain gameplay data like explosions
+const EXTRA_REMOVED_MESSAGES: &[MessageType] = &[
+    MessageType::TempEntities,
+    MessageType::EntityMessage,
+    MessageType::Menu,
+    MessageType::GetCvarValue,
+    MessageType::CmdKeyValues,
+];
+
+/// Reduce the size of a demo by removing the data not needed for analysing the demo
+///
+/// Voice, sounds and decals are removed together with the user and console commands.
+/// Temp entities, entity messages, menus, cvar queries and key values are only removed when
+/// created with [`Compact::all`].
+/// The number of bytes saved is tracked for every removed message and packet type.
+#[derive(Debug, Default)]
+pub struct Compact {
+    remove_extra: bool,
+    message_bits: HashMap<MessageType, usize>,
+    packet_bytes: HashMap<PacketType, usize>,
+}
+
+impl Compact {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also remove the message types that can contain gameplay data
+    pub fn all() -> Self {
+        Compact {
+            remove_extra: true,
+            ..Self::default()
+        }
+    }
+
+    /// The number of bytes saved for every removed message type
+    pub fn saved_messages(&self) -> impl Iterator<Item = (MessageType, usize)> + '_ {
+        self.message_bits
+            .iter()
+            .map(|(message_type, bits)| (*message_type, bits / 8))
+    }
+
+    /// The number of bytes saved for every removed packet type
+    pub fn saved_packets(&self) -> impl Iterator<Item = (PacketType, usize)> + '_ {
+        self.packet_bytes
+            .iter()
+            .map(|(packet_type, bytes)| (*packet_type, *bytes))
+    }
+
+    /// The total number of bytes saved
+    pub fn saved(&self) -> usize {
+        self.saved_messages()
+            .map(|(_, bytes)| bytes)
+            .chain(self.saved_packets().map(|(_, bytes)| bytes))
+            .sum()
+    }
+}
+
+impl Transform for Compact {
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        match packet {
+            Packet::Signon(message_packet) | Packet::Message(message_packet) => {
+                message_packet
+                    .messages
+                    .retain_mut(|message| self.transform_message(message, state));
+                true
+            }
+            Packet::UserCmd(_) | Packet::ConsoleCmd(_) => {
+                let mut data = Vec::new();
+                let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+                if packet.encode(&mut stream, state).is_ok() {
+                    *self.packet_bytes.entry(packet.packet_type()).or_default() +=
+                        stream.byte_len();
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn transform_message(&mut self, message: &mut Message, state: &ParserState) -> bool {
+        let message_type = message.get_message_type();
+        let removed = REMOVED_MESSAGES.contains(&message_type)
+            || (self.remove_extra && EXTRA_REMOVED_MESSAGES.contains(&message_type));
+        if !removed {
+            return true;
+        }
+
+        let mut data = Vec::new();
+        let mut stream = BitWriteStream::new(&mut data, LittleEndian);
+        if message_type.write(&mut stream).is_ok() && message.encode(&mut stream, state).is_ok() {
+            *self.message_bits.entry(message_type).or_default() += stream.bit_len();
+        }
+        false
+    }
+
+    fn report(&self) -> Option<String> {
+        let mut saved: Vec<(String, usize)> = self
+            .saved_messages()
+            .map(|(message_type, bytes)| (format!("{:?}", message_type), bytes))
+            .chain(
+                self.saved_packets()
+                    .map(|(packet_type, bytes)| (format!("{:?}", packet_type), bytes)),
+            )
+            .collect();
+        saved.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+
+        let mut report = format!("saved {} bytes", self.saved());
+        for (name, bytes) in saved {
+            let _ = write!(report, "\n  {}: {} bytes", name, bytes);
+        }
+        Some(report)
+    }
+}
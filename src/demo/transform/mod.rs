@@ -0,0 +1,138 @@
+//! Re-encoding demos while modifying, dropping or inserting packets and messages
+
+use crate::demo::header::Header;
+use crate::demo::message::Message;
+use crate::demo::packet::Packet;
+use crate::demo::parser::{DemoHandler, RawPacketStream};
+use crate::{Demo, DemoWriter, ParserState, Result};
+use bitbuffer::BitRead;
+
+pub use self::anonymise::Anonymise;
+pub use self::chat::RemoveChat;
+pub use self::compact::Compact;
+pub use self::pov::StripPov;
+
+mod anonymise;
+mod chat;
+mod compact;
+mod pov;
+
+/// A modification applied to the packets of a demo while re-encoding it
+///
+/// The parser state passed to the transform is the state of the input demo before the packet is handled.
+pub trait Transform {
+    /// Modify the header of the demo
+    ///
+    /// The `ticks`, `frames` and `duration` are recalculated after all packets are written
+    fn transform_header(&mut self, _header: &mut Header) {}
+
+    /// Packets to insert before the packet
+    ///
+    /// The inserted packets are passed through any following transforms
+    fn insert_packets<'a>(
+        &mut self,
+        _packet: &Packet<'a>,
+        _state: &ParserState,
+    ) -> Vec<Packet<'a>> {
+        Vec::new()
+    }
+
+    /// Modify a packet, returning `false` drops the packet
+    ///
+    /// By default the messages of message and signon packets are passed to [`transform_message`](Transform::transform_message)
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        if let Packet::Signon(message_packet) | Packet::Message(message_packet) = packet {
+            message_packet
+                .messages
+                .retain_mut(|message| self.transform_message(message, state));
+        }
+        true
+    }
+
+    /// Modify a message, returning `false` drops the message
+    fn transform_message(&mut self, _message: &mut Message, _state: &ParserState) -> bool {
+        true
+    }
+
+    /// A human readable summary of the changes made by the transform
+    fn report(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Get a transform by the name used on the command line
+pub fn transform_by_name(name: &str) -> Option<Box<dyn Transform>> {
+    match name {
+        "strip_pov" => Some(Box::new(StripPov)),
+        "remove_chat" => Some(Box::new(RemoveChat)),
+        "anonymise" => Some(Box::new(Anonymise::new())),
+        "compact" => Some(Box::new(Compact::new())),
+        "compact_all" => Some(Box::new(Compact::all())),
+        _ => None,
+    }
+}
+
+/// The re-encoded demo
+#[derive(Debug)]
+pub struct TransformedDemo {
+    pub data: Vec<u8>,
+    /// The input demo ended in the middle of a packet, the partial packet is left out of the output
+    pub incomplete: bool,
+}
+
+/// Re-encode a demo, passing every packet through the transforms in order
+pub fn transform_demo(
+    input: &[u8],
+    transforms: &mut [Box<dyn Transform>],
+) -> Result<TransformedDemo> {
+    let demo = Demo::new(input);
+    let mut stream = demo.get_stream();
+    let mut header = Header::read(&mut stream)?;
+    let mut packets = RawPacketStream::new(stream);
+
+    let mut handler = DemoHandler::default();
+    handler.handle_header(&header);
+
+    for transform in transforms.iter_mut() {
+        transform.transform_header(&mut header);
+    }
+    let mut writer = DemoWriter::new(header);
+
+    let mut out = Vec::new();
+    while let Some(packet) = packets.next(&handler.state_handler)? {
+        apply(
+            transforms,
+            packet.clone(),
+            handler.get_parser_state(),
+            &mut out,
+        );
+        for out_packet in out.drain(..) {
+            writer.write_packet(out_packet)?;
+        }
+        handler.handle_packet(packet)?;
+    }
+
+    Ok(TransformedDemo {
+        data: writer.finish()?,
+        incomplete: packets.incomplete,
+    })
+}
+
+fn apply<'a>(
+    transforms: &mut [Box<dyn Transform>],
+    mut packet: Packet<'a>,
+    state: &ParserState,
+    out: &mut Vec<Packet<'a>>,
+) {
+    match transforms.split_first_mut() {
+        Some((transform, rest)) => {
+            for inserted in transform.insert_packets(&packet, state) {
+                apply(rest, inserted, state, out);
+            }
+            if transform.transform_packet(&mut packet, state) {
+                apply(rest, packet, state, out);
+            }
+        }
+        None => out.push(packet),
+    }
+}
@@ -0,0 +1,32 @@
+use crate::demo::message::Message;
+use crate::demo::packet::Packet;
+use crate::demo::transform::Transform;
+use crate::ParserState;
+
+/// Make a pov demo look like an stv demo
+///
+/// Removes the view angles of the recording player, marks the server as stv and drops the console commands
+pub struct StripPov;
+
+impl Transform for StripPov {
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        match packet {
+            Packet::Signon(message_packet) | Packet::Message(message_packet) => {
+                message_packet.meta.view_angles = Default::default();
+                message_packet
+                    .messages
+                    .retain_mut(|message| self.transform_message(message, state));
+                true
+            }
+            Packet::ConsoleCmd(_) => false,
+            _ => true,
+        }
+    }
+
+    fn transform_message(&mut self, message: &mut Message, _state: &ParserState) -> bool {
+        if let Message::ServerInfo(info) = message {
+            info.stv = true;
+        }
+        true
+    }
+}
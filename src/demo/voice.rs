@@ -0,0 +1,409 @@
+//! Decoding of steam voice packets and writing the contained opus frames to an ogg container
+
+use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, LittleEndian};
+use std::io::{self, Write};
+
+use crate::{ReadResult, Stream};
+
+const PAYLOAD_SILENCE: u8 = 0;
+const PAYLOAD_OPUS_PLC: u8 = 6;
+const PAYLOAD_SAMPLE_RATE: u8 = 11;
+
+const OPUS_RESET: u16 = u16::MAX;
+
+/// Sample rate used by ogg opus granule positions
+pub const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Voice packet as send by the "steam" voice codec
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteamVoiceData {
+    pub steam_id: u64,
+    pub payloads: Vec<SteamVoicePayload>,
+    pub crc: u32,
+}
+
+impl SteamVoiceData {
+    pub fn parse(data: &[u8]) -> ReadResult<Self> {
+        BitReadStream::new(BitReadBuffer::new(data, LittleEndian)).read()
+    }
+
+    /// All opus frames in the packet
+    pub fn opus_frames(&self) -> impl Iterator<Item = &[u8]> {
+        self.payloads
+            .iter()
+            .filter_map(|payload| match payload {
+                SteamVoicePayload::OpusPlc(frames) => Some(frames),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|frame| match frame {
+                OpusFrame::Data { data, .. } => Some(data.as_slice()),
+                OpusFrame::Reset => None,
+            })
+    }
+
+    pub fn sample_rate(&self) -> Option<u16> {
+        self.payloads.iter().find_map(|payload| match payload {
+            SteamVoicePayload::SampleRate(rate) => Some(*rate),
+            _ => None,
+        })
+    }
+}
+
+impl BitRead<'_, LittleEndian> for SteamVoiceData {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let steam_id = stream.read()?;
+        let mut payloads = Vec::new();
+        // the packet ends with a crc32 of the data
+        while stream.bits_left() > 32 {
+            payloads.push(stream.read()?);
+        }
+        let crc = stream.read()?;
+        Ok(SteamVoiceData {
+            steam_id,
+            payloads,
+            crc,
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for SteamVoiceData {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        self.steam_id.write(stream)?;
+        for payload in &self.payloads {
+            payload.write(stream)?;
+        }
+        self.crc.write(stream)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SteamVoicePayload {
+    SampleRate(u16),
+    /// Number of silent samples
+    Silence(u16),
+    OpusPlc(Vec<OpusFrame>),
+    /// Other codecs, these are assumed to be length prefixed like the opus payloads
+    Unknown {
+        kind: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl BitRead<'_, LittleEndian> for SteamVoicePayload {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let kind: u8 = stream.read()?;
+        Ok(match kind {
+            PAYLOAD_SAMPLE_RATE => SteamVoicePayload::SampleRate(stream.read()?),
+            PAYLOAD_SILENCE => SteamVoicePayload::Silence(stream.read()?),
+            PAYLOAD_OPUS_PLC => {
+                let length: u16 = stream.read()?;
+                let mut data = stream.read_bits(length as usize * 8)?;
+                let mut frames = Vec::new();
+                while data.bits_left() >= 16 {
+                    frames.push(data.read()?);
+                }
+                SteamVoicePayload::OpusPlc(frames)
+            }
+            kind => {
+                let length: u16 = stream.read()?;
+                SteamVoicePayload::Unknown {
+                    kind,
+                    data: stream.read_bytes(length as usize)?.into_owned(),
+                }
+            }
+        })
+    }
+}
+
+impl BitWrite<LittleEndian> for SteamVoicePayload {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        match self {
+            SteamVoicePayload::SampleRate(rate) => {
+                PAYLOAD_SAMPLE_RATE.write(stream)?;
+                rate.write(stream)
+            }
+            SteamVoicePayload::Silence(samples) => {
+                PAYLOAD_SILENCE.write(stream)?;
+                samples.write(stream)
+            }
+            SteamVoicePayload::OpusPlc(frames) => {
+                PAYLOAD_OPUS_PLC.write(stream)?;
+                stream.reserve_byte_length(16, |stream| {
+                    for frame in frames {
+                        frame.write(stream)?;
+                    }
+                    Ok(())
+                })
+            }
+            SteamVoicePayload::Unknown { kind, data } => {
+                kind.write(stream)?;
+                (data.len() as u16).write(stream)?;
+                stream.write_bytes(data)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpusFrame {
+    /// Marker for the decoder to reset its state
+    Reset,
+    Data {
+        sequence: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl BitRead<'_, LittleEndian> for OpusFrame {
+    fn read(stream: &mut Stream) -> ReadResult<Self> {
+        let length: u16 = stream.read()?;
+        if length == OPUS_RESET {
+            return Ok(OpusFrame::Reset);
+        }
+        let sequence = stream.read()?;
+        let data = stream.read_bytes(length as usize)?.into_owned();
+        Ok(OpusFrame::Data { sequence, data })
+    }
+}
+
+impl BitWrite<LittleEndian> for OpusFrame {
+    fn write(&self, stream: &mut BitWriteStream<LittleEndian>) -> ReadResult<()> {
+        match self {
+            OpusFrame::Reset => OPUS_RESET.write(stream),
+            OpusFrame::Data { sequence, data } => {
+                (data.len() as u16).write(stream)?;
+                sequence.write(stream)?;
+                stream.write_bytes(data)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_steam_voice_roundtrip() {
+    crate::test_roundtrip_write(SteamVoiceData {
+        steam_id: 76561197960287930,
+        payloads: vec![
+            SteamVoicePayload::SampleRate(24000),
+            SteamVoicePayload::OpusPlc(vec![
+                OpusFrame::Data {
+                    sequence: 12,
+                    data: vec![0x78, 1, 2, 3],
+                },
+                OpusFrame::Reset,
+                OpusFrame::Data {
+                    sequence: 0,
+                    data: vec![0x78, 4, 5],
+                },
+            ]),
+            SteamVoicePayload::Silence(480),
+        ],
+        crc: 0x12345678,
+    });
+}
+
+/// Duration of an opus packet in 48khz samples, based on the packet's toc byte
+pub fn opus_packet_samples(packet: &[u8]) -> Option<u32> {
+    let toc = *packet.first()?;
+    let config = toc >> 3;
+    let frame_samples = match config {
+        // silk
+        0..=11 => [480, 960, 1920, 2880][config as usize % 4],
+        // hybrid
+        12..=15 => [480, 960][config as usize % 2],
+        // celt
+        _ => [120, 240, 480, 960][config as usize % 4],
+    };
+    let frames = match toc & 0x03 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => (*packet.get(1)? & 0x3F) as u32,
+    };
+    Some(frame_samples * frames)
+}
+
+#[test]
+fn test_opus_packet_samples() {
+    assert_eq!(Some(960), opus_packet_samples(&[0xF8, 0xFF, 0xFE]));
+    assert_eq!(Some(480), opus_packet_samples(&[0x00]));
+    assert_eq!(Some(1920), opus_packet_samples(&[0x79]));
+    assert_eq!(Some(2880), opus_packet_samples(&[0xFB, 0x03]));
+    assert_eq!(None, opus_packet_samples(&[]));
+}
+
+/// 20ms of silence
+const OPUS_SILENCE: [u8; 3] = [0xF8, 0xFF, 0xFE];
+
+const fn ogg_crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80000000 != 0 {
+                (crc << 1) ^ 0x04C11DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static OGG_CRC_TABLE: [u32; 256] = ogg_crc_table();
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, byte| {
+        (crc << 8) ^ OGG_CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+    })
+}
+
+const PAGE_CONTINUED: u8 = 0;
+const PAGE_BOS: u8 = 2;
+const PAGE_EOS: u8 = 4;
+
+/// Writes opus packets into an ogg container with a single mono stream
+///
+/// Every packet is written in its own page, gaps in the audio can be filled with silence using [`pad_to`](Self::pad_to)
+pub struct OggOpusWriter<W: Write> {
+    writer: W,
+    serial: u32,
+    page_sequence: u32,
+    granule_position: u64,
+    pending: Option<Vec<u8>>,
+}
+
+impl<W: Write> OggOpusWriter<W> {
+    pub fn new(writer: W, input_sample_rate: u32) -> io::Result<Self> {
+        let mut ogg = OggOpusWriter {
+            writer,
+            serial: 0x7466_6470,
+            page_sequence: 0,
+            granule_position: 0,
+            pending: None,
+        };
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1); // version
+        head.push(1); // channels
+        head.extend_from_slice(&0u16.to_le_bytes()); // pre-skip
+        head.extend_from_slice(&input_sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // channel mapping family
+        ogg.write_page(PAGE_BOS, 0, &head)?;
+
+        let vendor = env!("CARGO_PKG_NAME").as_bytes();
+        let mut tags = Vec::with_capacity(16 + vendor.len());
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes()); // user comments
+        ogg.write_page(PAGE_CONTINUED, 0, &tags)?;
+
+        Ok(ogg)
+    }
+
+    /// Position of the end of the written audio in 48khz samples
+    pub fn granule_position(&self) -> u64 {
+        self.granule_position
+    }
+
+    pub fn write_packet(&mut self, packet: &[u8]) -> io::Result<()> {
+        let samples = opus_packet_samples(packet)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty opus packet"))?;
+        if let Some(pending) = self.pending.take() {
+            self.write_page(PAGE_CONTINUED, self.granule_position, &pending)?;
+        }
+        self.granule_position += samples as u64;
+        self.pending = Some(packet.to_vec());
+        Ok(())
+    }
+
+    /// Fill the stream with silence until the given position in 48khz samples
+    pub fn pad_to(&mut self, position: u64) -> io::Result<()> {
+        while self.granule_position + 960 <= position {
+            self.write_packet(&OPUS_SILENCE)?;
+        }
+        Ok(())
+    }
+
+    /// Write the final page and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        let last = match self.pending.take() {
+            Some(last) => last,
+            None => {
+                self.granule_position += 960;
+                OPUS_SILENCE.to_vec()
+            }
+        };
+        self.write_page(PAGE_EOS, self.granule_position, &last)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_page(
+        &mut self,
+        header_type: u8,
+        granule_position: u64,
+        packet: &[u8],
+    ) -> io::Result<()> {
+        let segment_count = packet.len() / 255 + 1;
+        if segment_count > 255 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "opus packet too large for a single ogg page",
+            ));
+        }
+
+        let mut page = Vec::with_capacity(27 + segment_count + packet.len());
+        page.extend_from_slice(b"OggS");
+        page.push(0); // version
+        page.push(header_type);
+        page.extend_from_slice(&granule_position.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.page_sequence.to_le_bytes());
+        page.extend_from_slice(&0u32.to_le_bytes()); // crc placeholder
+        page.push(segment_count as u8);
+        page.resize(page.len() + segment_count - 1, 255);
+        page.push((packet.len() % 255) as u8);
+        page.extend_from_slice(packet);
+
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+        self.page_sequence += 1;
+        self.writer.write_all(&page)
+    }
+}
+
+#[test]
+fn test_ogg_opus_writer() {
+    let mut ogg = OggOpusWriter::new(Vec::new(), 24000).unwrap();
+    ogg.write_packet(&[0x78, 1, 2, 3]).unwrap();
+    assert_eq!(960, ogg.granule_position());
+    ogg.pad_to(48000).unwrap();
+    assert_eq!(48000, ogg.granule_position());
+    let data = ogg.finish().unwrap();
+
+    let pages = data.windows(4).filter(|window| window == b"OggS").count();
+    // 2 header pages, 1 audio page and 49 silence pages
+    assert_eq!(52, pages);
+    assert_eq!(b"OpusHead", &data[28..36]);
+
+    // crc of each page is calculated with the crc field zeroed
+    let mut first_page = data[0..47].to_vec();
+    let crc = u32::from_le_bytes([
+        first_page[22],
+        first_page[23],
+        first_page[24],
+        first_page[25],
+    ]);
+    first_page[22..26].copy_from_slice(&[0; 4]);
+    assert_eq!(crc, ogg_crc(&first_page));
+}
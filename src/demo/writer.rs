@@ -0,0 +1,103 @@
+use crate::demo::data::DemoTick;
+use crate::demo::header::Header;
+use crate::demo::packet::stop::StopPacket;
+use crate::demo::packet::Packet;
+use crate::demo::parser::{DemoHandler, Encode, NullHandler};
+use crate::{ParserState, Result};
+use bitbuffer::{BitWrite, BitWriteStream, LittleEndian};
+
+/// Write packets into a playable demo file
+///
+/// The writer keeps track of the parser state needed to encode the packets,
+/// packets that depend on earlier packets (like entity updates depending on the data tables)
+/// need to be written in the order they appear in the demo.
+///
+/// The `ticks`, `frames` and `duration` of the header are updated based on the written packets
+/// when the demo is finished.
+pub struct DemoWriter<'a> {
+    header: Header,
+    handler: DemoHandler<'a, NullHandler>,
+    data: Vec<u8>,
+    last_tick: DemoTick,
+    frames: u32,
+    stopped: bool,
+}
+
+impl<'a> DemoWriter<'a> {
+    pub fn new(header: Header) -> Self {
+        let mut handler = DemoHandler::default();
+        handler.handle_header(&header);
+        DemoWriter {
+            header,
+            handler,
+            data: Vec::new(),
+            last_tick: DemoTick::default(),
+            frames: 0,
+            stopped: false,
+        }
+    }
+
+    /// The parser state build from the packets written so far
+    pub fn state(&self) -> &ParserState {
+        self.handler.get_parser_state()
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Encode a packet and add it to the demo
+    ///
+    /// Any packets written after a stop packet are ignored
+    pub fn write_packet(&mut self, packet: Packet<'a>) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        // packets are byte aligned, but the stream positions are relative to the start of the buffer
+        let mut packet_data = Vec::new();
+        {
+            let mut stream = BitWriteStream::new(&mut packet_data, LittleEndian);
+            packet.encode(&mut stream, self.handler.get_parser_state())?;
+        }
+        self.data.extend_from_slice(&packet_data);
+
+        match &packet {
+            Packet::Message(_) => self.frames += 1,
+            Packet::Stop(_) => self.stopped = true,
+            _ => {}
+        }
+        self.last_tick = packet.tick();
+        self.handler.handle_packet(packet)
+    }
+
+    /// Finish the demo and get the encoded file
+    ///
+    /// A stop packet is added if none has been written yet
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        if !self.stopped {
+            self.write_packet(Packet::Stop(StopPacket {
+                tick: self.last_tick,
+            }))?;
+        }
+
+        let ticks = u32::from(self.last_tick);
+        let interval_per_tick = match self.state().demo_meta.interval_per_tick {
+            interval if interval > 0.0 => interval,
+            // fallback for demos without server info
+            _ if self.header.ticks > 0 => self.header.duration / self.header.ticks as f32,
+            _ => 1.0 / 66.0,
+        };
+        self.header.ticks = ticks;
+        self.header.frames = self.frames;
+        self.header.duration = ticks as f32 * interval_per_tick;
+
+        let mut out = Vec::with_capacity(self.data.len() + 1072);
+        {
+            let mut stream = BitWriteStream::new(&mut out, LittleEndian);
+            self.header.write(&mut stream)?;
+        }
+        out.extend_from_slice(&self.data);
+        Ok(out)
+    }
+}
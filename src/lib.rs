@@ -6,12 +6,15 @@ pub use crate::demo::{
         DemoParser, GameEventError, MatchState, MessageTypeAnalyser, Parse, ParseError,
         ParserState, Result,
     },
+    writer::DemoWriter,
     Demo, Stream,
 };
 
 pub(crate) mod consthash;
 pub mod demo;
 pub(crate) mod nullhasher;
+#[cfg(test)]
+pub(crate) mod test_util;
 
 #[cfg(test)]
 #[track_caller]
@@ -0,0 +1,159 @@
+//! Fixtures shared by the unit tests of the message handlers
+
+use crate::demo::data::userinfo::PlayerInfo;
+use crate::demo::data::UserInfo;
+use crate::demo::gameevent_gen::PlayerDeathEvent;
+use crate::demo::gamevent::GameEvent;
+use crate::demo::message::gameevent::{GameEventMessage, GameEventTypeId};
+use crate::demo::message::packetentities::{
+    EntityId, PacketEntitiesMessage, PacketEntity, UpdateType,
+};
+use crate::demo::message::{Message, MessageType};
+use crate::demo::packet::datatable::{SendTable, ServerClass};
+use crate::demo::packet::stringtable::{StringTable, StringTableEntry};
+use crate::demo::sendprop::{SendProp, SendPropIdentifier, SendPropValue};
+use crate::ParserState;
+
+/// A parser state with the given server classes, with empty send tables
+pub fn parser_state(
+    analyser_handles: fn(message_type: MessageType) -> bool,
+    server_classes: &[&'static str],
+) -> ParserState {
+    let mut state = ParserState::new(24, analyser_handles, false);
+    state.server_classes = server_classes
+        .iter()
+        .enumerate()
+        .map(|(id, name)| ServerClass {
+            id: (id as u16).into(),
+            name: (*name).into(),
+            data_table: (*name).into(),
+        })
+        .collect();
+    state.send_tables = state
+        .server_classes
+        .iter()
+        .map(|class| SendTable {
+            name: class.data_table.clone(),
+            needs_decoder: false,
+            raw_props: vec![],
+            flattened_props: vec![],
+        })
+        .collect();
+    state
+}
+
+/// Create a string table in the parser state with the given entries
+pub fn string_table(state: &mut ParserState, name: &str, entries: &[(usize, &str)]) {
+    state.string_table_store.create_table(&StringTable {
+        name: name.into(),
+        entries: vec![],
+        max_entries: 1024,
+        fixed_user_data_size: None,
+        client_entries: None,
+        compressed: false,
+    });
+    for (index, text) in entries {
+        state.string_table_store.set_entry(
+            name,
+            *index,
+            &StringTableEntry {
+                text: Some((*text).into()),
+                extra_data: None,
+            },
+        );
+    }
+}
+
+pub fn packet_entity(
+    index: u32,
+    server_class: u16,
+    update_type: UpdateType,
+    props: Vec<(SendPropIdentifier, SendPropValue)>,
+) -> PacketEntity {
+    PacketEntity {
+        server_class: server_class.into(),
+        entity_index: EntityId::from(index),
+        props: props
+            .into_iter()
+            .enumerate()
+            .map(|(index, (identifier, value))| SendProp {
+                index: index as u32,
+                identifier,
+                value,
+            })
+            .collect(),
+        in_pvs: true,
+        update_type,
+        serial_number: 0,
+        delay: None,
+        delta: None,
+        baseline_index: 0,
+    }
+}
+
+pub fn packet_entities(entities: Vec<PacketEntity>, removed: Vec<u32>) -> Message<'static> {
+    Message::PacketEntities(PacketEntitiesMessage {
+        entities,
+        removed_entities: removed.into_iter().map(EntityId::from).collect(),
+        ..PacketEntitiesMessage::default()
+    })
+}
+
+pub fn game_event(event: GameEvent) -> Message<'static> {
+    Message::GameEvent(GameEventMessage {
+        event_type_id: GameEventTypeId::new(0),
+        event,
+    })
+}
+
+/// A `player_death` event for testing the death handling of analysers
+pub fn death_event(
+    user_id: u16,
+    attacker: u16,
+    death_flags: u16,
+    custom_kill: u16,
+) -> PlayerDeathEvent {
+    PlayerDeathEvent {
+        user_id,
+        victim_ent_index: 0,
+        inflictor_ent_index: 0,
+        attacker,
+        weapon: "knife".into(),
+        weapon_id: 0,
+        damage_bits: 0,
+        custom_kill,
+        assister: u16::MAX,
+        weapon_log_class_name: "knife".into(),
+        stun_flags: 0,
+        death_flags,
+        silent_kill: false,
+        player_penetrate_count: 0,
+        assister_fallback: "".into(),
+        kill_streak_total: 0,
+        kill_streak_wep: 0,
+        kill_streak_assist: 0,
+        kill_streak_victim: 0,
+        ducks_streaked: 0,
+        duck_streak_total: 0,
+        duck_streak_assist: 0,
+        duck_streak_victim: 0,
+        rocket_jump: false,
+        weapon_def_index: 0,
+        crit_type: 0,
+    }
+}
+
+/// A `userinfo` string table entry for a player in an entity slot
+pub fn user_info_entry(user_id: u16, entity_id: u32) -> StringTableEntry<'static> {
+    UserInfo {
+        entity_id: entity_id.into(),
+        player_info: PlayerInfo {
+            name: format!("player {}", user_id),
+            user_id: user_id.into(),
+            steam_id: format!("[U:1:{}]", user_id).parse().unwrap(),
+            ..PlayerInfo::default()
+        },
+    }
+    .encode_to_string_table()
+    .unwrap()
+}
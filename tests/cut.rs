@@ -0,0 +1,52 @@
+use std::fs;
+use test_case::test_case;
+
+use bitbuffer::BitRead;
+use tf_demo_parser::demo::cut::cut_demo;
+use tf_demo_parser::demo::data::DemoTick;
+use tf_demo_parser::demo::header::Header;
+use tf_demo_parser::demo::message::packetentities::PacketEntity;
+use tf_demo_parser::demo::packet::Packet;
+use tf_demo_parser::demo::parser::entitystateanalyser::EntityStateAnalyser;
+use tf_demo_parser::demo::parser::{DemoHandler, RawPacketStream};
+use tf_demo_parser::Demo;
+
+/// Get the state of all entities at the end tick
+fn entities_at(demo: &[u8], end_tick: DemoTick) -> Vec<PacketEntity> {
+    let demo = Demo::new(demo);
+    let mut stream = demo.get_stream();
+    let header = Header::read(&mut stream).unwrap();
+    let mut packets = RawPacketStream::new(stream);
+    let mut handler = DemoHandler::parse_all_with_analyser(EntityStateAnalyser::new());
+    handler.handle_header(&header);
+    while let Some(packet) = packets.next(&handler.state_handler).unwrap() {
+        // signon packets don't have meaningful ticks
+        if matches!(packet, Packet::Message(_)) && packet.tick() > end_tick {
+            break;
+        }
+        handler.handle_packet(packet).unwrap();
+    }
+    handler
+        .into_output()
+        .entities
+        .into_values()
+        .map(|mut entity| {
+            entity.props.sort_by_key(|prop| prop.index);
+            entity
+        })
+        .collect()
+}
+
+#[test_case("small.dem", 50, 100; "small.dem")]
+#[test_case("small.dem", 0, 30; "small.dem from start")]
+fn cut_test(input_file: &str, start_tick: u32, end_tick: u32) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let cut = cut_demo(&file, start_tick.into(), end_tick.into()).unwrap();
+
+    let header = Header::read(&mut Demo::new(&cut).get_stream()).unwrap();
+    assert_eq!(end_tick - start_tick, header.ticks);
+
+    let expected = entities_at(&file, end_tick.into());
+    assert!(!expected.is_empty());
+    assert_eq!(expected, entities_at(&cut, (end_tick - start_tick).into()));
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use test_case::test_case;
+
+use tf_demo_parser::demo::diff::{diff_demos, DivergenceKind};
+use tf_demo_parser::demo::message::Message;
+use tf_demo_parser::demo::packet::Packet;
+use tf_demo_parser::demo::sendprop::SendPropValue;
+use tf_demo_parser::demo::transform::{transform_demo, Transform};
+use tf_demo_parser::ParserState;
+
+/// Change the first integer prop of an entity update
+#[derive(Default)]
+struct ChangeProp {
+    changed: bool,
+}
+
+impl Transform for ChangeProp {
+    fn transform_message(&mut self, message: &mut Message, _state: &ParserState) -> bool {
+        if let (false, Message::PacketEntities(entities)) = (self.changed, message) {
+            for entity in entities.entities.iter_mut() {
+                for prop in entity.props.iter_mut() {
+                    if let SendPropValue::Integer(value) = &mut prop.value {
+                        *value ^= 1;
+                        self.changed = true;
+                        return true;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        if let Packet::Message(message_packet) = packet {
+            if u32::from(message_packet.tick) > 10 {
+                for message in message_packet.messages.iter_mut() {
+                    self.transform_message(message, state);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Remove the last message of the first packet with multiple messages
+#[derive(Default)]
+struct RemoveMessage {
+    removed: bool,
+}
+
+impl Transform for RemoveMessage {
+    fn transform_packet(&mut self, packet: &mut Packet, _state: &ParserState) -> bool {
+        if let Packet::Message(message_packet) = packet {
+            if !self.removed
+                && u32::from(message_packet.tick) > 10
+                && message_packet.messages.len() > 1
+            {
+                message_packet.messages.pop();
+                self.removed = true;
+            }
+        }
+        true
+    }
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn diff_identical_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let diff = diff_demos(&file, &file).unwrap();
+    assert!(diff.is_empty(), "{}", diff);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn diff_prop_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let reencoded = transform_demo(&file, &mut []).unwrap().data;
+
+    let changed = transform_demo(
+        &file,
+        &mut [Box::new(ChangeProp::default()) as Box<dyn Transform>],
+    )
+    .unwrap()
+    .data;
+
+    let diff = diff_demos(&reencoded, &changed).unwrap();
+    let divergence = diff.divergence.unwrap();
+    assert_eq!(divergence.offsets[0], divergence.offsets[1]);
+    match divergence.kind {
+        DivergenceKind::Prop {
+            props: [Some(left), Some(right)],
+            ..
+        } => {
+            assert_eq!(left.identifier, right.identifier);
+            assert_ne!(left.value, right.value);
+        }
+        kind => panic!("unexpected divergence {:?}", kind),
+    }
+
+    // the first changed bit is in the value of the prop at the reported offset,
+    // after the prop marker and index, integer props are at most 32 bits
+    let first_changed_bit = reencoded
+        .iter()
+        .zip(changed.iter())
+        .enumerate()
+        .find(|(_, (left, right))| left != right)
+        .map(|(index, (left, right))| index * 8 + (left ^ right).trailing_zeros() as usize)
+        .unwrap();
+    let prop_offset = divergence.offsets[0];
+    assert!(
+        first_changed_bit > prop_offset && first_changed_bit < prop_offset + 1 + 34 + 32,
+        "prop at bit {}, first change at bit {}",
+        prop_offset,
+        first_changed_bit
+    );
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn diff_missing_message_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let reencoded = transform_demo(&file, &mut []).unwrap().data;
+
+    let removed = transform_demo(
+        &file,
+        &mut [Box::new(RemoveMessage::default()) as Box<dyn Transform>],
+    )
+    .unwrap()
+    .data;
+
+    let diff = diff_demos(&reencoded, &removed).unwrap();
+    let divergence = diff.divergence.unwrap();
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Message {
+            messages: [Some(_), None],
+            ..
+        }
+    ));
+    // the missing message is reported at the end of the message data, which is padded to a full byte
+    let [message_offset, end_offset] = divergence.offsets;
+    assert!(
+        end_offset >= message_offset && end_offset < message_offset + 8,
+        "message at bit {}, end of data at bit {}",
+        message_offset,
+        end_offset
+    );
+}
@@ -0,0 +1,66 @@
+use std::fs;
+
+use bitbuffer::BitRead;
+use tf_demo_parser::demo::cut::cut_demo;
+use tf_demo_parser::demo::header::Header;
+use tf_demo_parser::demo::merge::merge_demos;
+use tf_demo_parser::demo::message::packetentities::PacketEntity;
+use tf_demo_parser::demo::parser::entitystateanalyser::EntityStateAnalyser;
+use tf_demo_parser::{Demo, DemoParser};
+
+fn entities(demo: &[u8]) -> Vec<PacketEntity> {
+    let demo = Demo::new(demo);
+    let (_, state) =
+        DemoParser::new_all_with_analyser(demo.get_stream(), EntityStateAnalyser::new())
+            .parse()
+            .unwrap();
+    state
+        .entities
+        .into_values()
+        .map(|mut entity| {
+            entity.props.sort_by_key(|prop| prop.index);
+            entity
+        })
+        .collect()
+}
+
+#[test]
+fn merge_test() {
+    let file = fs::read("test_data/small.dem").expect("Unable to read file");
+    let merged = merge_demos(&[&file, &file]).unwrap();
+
+    let header = Header::read(&mut Demo::new(&file).get_stream()).unwrap();
+    let merged_header = Header::read(&mut Demo::new(&merged).get_stream()).unwrap();
+    assert_eq!(header.ticks * 2 + 1, merged_header.ticks);
+    assert_eq!(header.frames * 2, merged_header.frames);
+
+    // the second demo starts with a full update
+    assert_eq!(entities(&file), entities(&merged));
+
+    let (_, state) = DemoParser::new(Demo::new(&merged).get_stream())
+        .parse()
+        .unwrap();
+    let (_, single_state) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(single_state.start_tick, state.start_tick);
+    assert_eq!(single_state.users, state.users);
+}
+
+#[test]
+fn merge_halves_test() {
+    let file = fs::read("test_data/small.dem").expect("Unable to read file");
+    let header = Header::read(&mut Demo::new(&file).get_stream()).unwrap();
+    let middle = header.ticks / 2;
+
+    let first = cut_demo(&file, 0.into(), middle.into()).unwrap();
+    let second = cut_demo(&file, (middle + 1).into(), header.ticks.into()).unwrap();
+    // the halves start with different entities
+    assert_ne!(entities(&first), entities(&second));
+
+    let merged = merge_demos(&[&first, &second]).unwrap();
+    let merged_header = Header::read(&mut Demo::new(&merged).get_stream()).unwrap();
+    assert_eq!(header.ticks, merged_header.ticks);
+
+    assert_eq!(entities(&file), entities(&merged));
+}
@@ -3,6 +3,7 @@ use test_case::test_case;
 
 use bitbuffer::{BitRead, BitReadBuffer, BitReadStream, BitWrite, BitWriteStream, LittleEndian};
 use std::collections::HashMap;
+use tf_demo_parser::demo::diff::diff_demos;
 use tf_demo_parser::demo::header::Header;
 use tf_demo_parser::demo::message::Message;
 use tf_demo_parser::demo::packet::datatable::SendTableName;
@@ -139,3 +140,67 @@ fn re_encode_test(input_file: &str) {
         handler.handle_packet(packet).unwrap();
     }
 }
+
+/// Re-encoding an unmodified demo should result in the exact same bytes
+///
+/// Demos that aren't available, like when the git lfs data isn't fetched, are skipped
+#[test_case("test_data/small.dem"; "small.dem")]
+#[test_case("test_data/gully.dem"; "gully.dem")]
+#[test_case("test_data/comp.dem"; "comp.dem")]
+#[test_case("test_data/malformed_cvar.dem"; "malformed_cvar.dem")]
+#[test_case("test_data/unicode-saytext.dem"; "unicode-saytext.dem")]
+#[test_case("test_data/nousers.dem"; "nousers.dem")]
+#[test_case("test_data/decal.dem"; "decal.dem")]
+#[test_case("test_data/saytext2.dem"; "saytext2.dem")]
+#[test_case("test_data/emptysaytext.dem"; "emptysaytext.dem")]
+fn byte_identical_re_encode_test(input_file: &str) {
+    let file = match fs::read(input_file) {
+        Ok(file) if !file.starts_with(b"version https://git-lfs") => file,
+        _ => {
+            eprintln!("skipping {}, demo not available", input_file);
+            return;
+        }
+    };
+    let demo = Demo::new(&file);
+    let mut stream = demo.get_stream();
+    let header = Header::read(&mut stream).unwrap();
+
+    let mut out_buffer = Vec::with_capacity(file.len());
+    {
+        let mut out_stream = BitWriteStream::new(&mut out_buffer, LittleEndian);
+        header.write(&mut out_stream).unwrap();
+    }
+
+    let mut packets = RawPacketStream::new(stream);
+    let mut handler = DemoHandler::parse_all_with_analyser(NullHandler);
+    let mut end = packets.pos() / 8;
+    while let Some(packet) = packets.next(&handler.state_handler).unwrap() {
+        if let Packet::Message(message_packet) | Packet::Signon(message_packet) = &packet {
+            // empty messages are kept in the padding, not in the parsed messages
+            assert!(!message_packet
+                .messages
+                .iter()
+                .any(|message| matches!(message, Message::Empty)));
+        }
+
+        let mut packet_buffer = Vec::new();
+        {
+            let mut out_stream = BitWriteStream::new(&mut packet_buffer, LittleEndian);
+            packet
+                .encode(&mut out_stream, &handler.state_handler)
+                .unwrap();
+        }
+        end = packets.pos() / 8;
+        out_buffer.extend_from_slice(&packet_buffer);
+
+        handler.handle_packet(packet).unwrap();
+    }
+
+    if file[..end] != out_buffer[..] {
+        panic!(
+            "{} is not re-encoded identically\n{}",
+            input_file,
+            diff_demos(&file[..end], &out_buffer).unwrap()
+        );
+    }
+}
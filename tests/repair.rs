@@ -0,0 +1,96 @@
+use std::fs;
+use test_case::test_case;
+
+use bitbuffer::BitRead;
+use tf_demo_parser::demo::header::Header;
+use tf_demo_parser::demo::message::Message;
+use tf_demo_parser::demo::packet::Packet;
+use tf_demo_parser::demo::parser::RawPacketStream;
+use tf_demo_parser::demo::repair::repair_demo;
+use tf_demo_parser::{Demo, DemoParser, DemoWriter};
+
+#[test_case("small.dem"; "small.dem")]
+fn repair_intact_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let (repaired, report) = repair_demo(&file).unwrap();
+    assert!(report.is_empty(), "{}", report);
+    assert_eq!(file, repaired);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn repair_truncated_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let (expected_header, _) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+
+    // a crashed client leaves a partial packet and no duration, ticks or frames in the header
+    let mut truncated = file[0..file.len() - 1000].to_vec();
+    truncated[1056..1068].fill(0);
+
+    let (repaired, report) = repair_demo(&truncated).unwrap();
+    assert!(report.stop_added);
+    assert!(report.truncated_bytes > 0);
+    assert!(report.error.is_none());
+
+    let headers = report.header.unwrap();
+    assert_eq!(0, headers[0].ticks);
+    assert!(headers[1].ticks > 0);
+    assert!(headers[1].ticks <= expected_header.ticks);
+    assert!(headers[1].frames > 0);
+    assert!(headers[1].duration > 0.0);
+
+    let (header, _) = DemoParser::new(Demo::new(&repaired).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(headers[1], header);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn repair_trailing_data_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let mut with_trailing = file.clone();
+    with_trailing.extend_from_slice(&[0xff; 10]);
+
+    let (repaired, report) = repair_demo(&with_trailing).unwrap();
+    assert_eq!(10, report.truncated_bytes);
+    assert!(!report.stop_added);
+    assert!(report.header.is_none());
+    assert_eq!(file, repaired);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn repair_without_server_info_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let demo = Demo::new(&file);
+    let mut stream = demo.get_stream();
+    let header = Header::read(&mut stream).unwrap();
+
+    // a demo without server info, so the tick interval is unknown
+    let mut packets = RawPacketStream::new(stream);
+    let mut writer = DemoWriter::new(header.clone());
+    while let Some(mut packet) = packets.next(writer.state()).unwrap() {
+        if let Packet::Signon(message_packet) | Packet::Message(message_packet) = &mut packet {
+            message_packet
+                .messages
+                .retain(|message| !matches!(message, Message::ServerInfo(_)));
+        }
+        writer.write_packet(packet).unwrap();
+    }
+    let without_server_info = writer.finish().unwrap();
+    let truncated = &without_server_info[0..without_server_info.len() - 1000];
+
+    // the tick interval of the original header is used
+    let (_, report) = repair_demo(truncated).unwrap();
+    let headers = report.header.unwrap();
+    let expected_duration = headers[1].ticks as f32 * header.duration / header.ticks as f32;
+    assert!(headers[1].ticks > 0);
+    assert!((expected_duration - headers[1].duration).abs() < 0.001);
+
+    // without a duration in the header, 66 ticks per second is assumed
+    let mut truncated = truncated.to_vec();
+    truncated[1056..1068].fill(0);
+    let (_, report) = repair_demo(&truncated).unwrap();
+    let headers = report.header.unwrap();
+    assert!((headers[1].ticks as f32 / 66.0 - headers[1].duration).abs() < 0.001);
+}
@@ -0,0 +1,297 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use test_case::test_case;
+
+use bitbuffer::BitRead;
+use tf_demo_parser::demo::packet::stringtable::StringTableEntry;
+
+use tf_demo_parser::demo::message::tempentities::TempEntitiesMessage;
+use tf_demo_parser::demo::message::usermessage::{ChatMessageKind, SayText2Message, UserMessage};
+use tf_demo_parser::demo::message::Message;
+use tf_demo_parser::demo::packet::message::MessagePacket;
+use tf_demo_parser::demo::packet::Packet;
+use tf_demo_parser::demo::transform::{transform_by_name, transform_demo, Transform};
+use tf_demo_parser::{Demo, DemoParser, ParserState};
+
+/// Insert a single chat message into the demo
+struct InsertChat {
+    inserted: bool,
+}
+
+impl Transform for InsertChat {
+    fn insert_packets<'a>(&mut self, packet: &Packet<'a>, _state: &ParserState) -> Vec<Packet<'a>> {
+        match packet {
+            Packet::Message(message_packet) if !self.inserted && u32::from(packet.tick()) > 10 => {
+                self.inserted = true;
+                vec![Packet::Message(MessagePacket {
+                    tick: message_packet.tick,
+                    messages: vec![Message::UserMessage(UserMessage::SayText2(Box::new(
+                        SayText2Message {
+                            client: 1u32.into(),
+                            raw: 1,
+                            kind: ChatMessageKind::ChatAll,
+                            from: Some("player".into()),
+                            text: "hello".into(),
+                        },
+                    )))],
+                    meta: message_packet.meta.clone(),
+                    padding: None,
+                })]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Drop the original compressed data of all string tables, forcing them to be compressed again
+#[derive(Default)]
+struct Recompress {
+    compressed: usize,
+}
+
+impl Transform for Recompress {
+    fn transform_message(&mut self, message: &mut Message, _state: &ParserState) -> bool {
+        if let Message::CreateStringTable(create) = message {
+            if create.compressed_data.take().is_some() {
+                self.compressed += 1;
+            }
+        }
+        true
+    }
+
+    fn report(&self) -> Option<String> {
+        Some(format!("{} compressed tables", self.compressed))
+    }
+}
+
+/// Collect the extra data of all userinfo entries
+struct CollectUserInfo {
+    data: Rc<RefCell<Vec<Vec<u8>>>>,
+}
+
+impl CollectUserInfo {
+    fn collect<'a>(&self, entries: impl Iterator<Item = &'a StringTableEntry<'a>>) {
+        for extra in entries.filter_map(|entry| entry.extra_data.as_ref()) {
+            let mut data = extra.data.clone();
+            let bytes = data.read_bytes(extra.byte_len as usize).unwrap();
+            self.data.borrow_mut().push(bytes.into_owned());
+        }
+    }
+}
+
+impl Transform for CollectUserInfo {
+    fn transform_packet(&mut self, packet: &mut Packet, state: &ParserState) -> bool {
+        match packet {
+            Packet::Signon(message_packet) | Packet::Message(message_packet) => {
+                for message in message_packet.messages.iter_mut() {
+                    self.transform_message(message, state);
+                }
+            }
+            Packet::StringTables(string_tables) => {
+                for table in string_tables
+                    .tables
+                    .iter()
+                    .filter(|table| table.name == "userinfo")
+                {
+                    self.collect(table.entries.iter().map(|(_, entry)| entry));
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn transform_message(&mut self, message: &mut Message, state: &ParserState) -> bool {
+        match message {
+            Message::CreateStringTable(create) if create.table.name == "userinfo" => {
+                self.collect(create.table.entries.iter().map(|(_, entry)| entry));
+            }
+            Message::UpdateStringTable(update)
+                if state
+                    .string_table_store
+                    .get_by_id(update.table_id)
+                    .map(|table| table.name == "userinfo")
+                    .unwrap_or_default() =>
+            {
+                self.collect(update.entries.iter().map(|(_, entry)| entry));
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+fn transform(file: &[u8], mut transforms: Vec<Box<dyn Transform>>) -> Vec<u8> {
+    let transformed = transform_demo(file, &mut transforms).unwrap();
+    assert!(!transformed.incomplete);
+    transformed.data
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_chat_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let with_chat = transform(&file, vec![Box::new(InsertChat { inserted: false })]);
+    let (_, state) = DemoParser::new(Demo::new(&with_chat).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(1, state.chat.len());
+    assert_eq!("hello", state.chat[0].text);
+
+    // the inserted packets are passed through the following transforms
+    let without_chat = transform(
+        &file,
+        vec![
+            Box::new(InsertChat { inserted: false }),
+            transform_by_name("remove_chat").unwrap(),
+        ],
+    );
+    let (_, state) = DemoParser::new(Demo::new(&without_chat).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(0, state.chat.len());
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_strip_pov_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let stripped = transform(&file, vec![transform_by_name("strip_pov").unwrap()]);
+
+    let (expected_header, expected_state) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+    let (header, state) = DemoParser::new(Demo::new(&stripped).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(expected_header.ticks, header.ticks);
+    pretty_assertions::assert_eq!(expected_state, state);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_anonymise_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let anonymised = transform(&file, vec![transform_by_name("anonymise").unwrap()]);
+
+    let (_, expected_state) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+    let (header, state) = DemoParser::new(Demo::new(&anonymised).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!("", header.nick);
+    assert_eq!("", header.server);
+
+    assert_eq!(expected_state.users.len(), state.users.len());
+    for (user_id, user) in state.users.iter() {
+        let expected = &expected_state.users[user_id];
+        assert_ne!(expected.steam_id, user.steam_id);
+        assert!(user.steam_id.steam_id().is_some());
+        assert_ne!(expected.name, user.name);
+        assert!(user.name.starts_with("Player "));
+    }
+    assert_eq!(expected_state.deaths.len(), state.deaths.len());
+
+    // the account id is also stored as friends id and in the text form of the steam id
+    let user_info = Rc::new(RefCell::new(Vec::new()));
+    transform(
+        &anonymised,
+        vec![Box::new(CollectUserInfo {
+            data: user_info.clone(),
+        })],
+    );
+    let user_info = user_info.borrow();
+    assert!(!user_info.is_empty());
+    for account_id in expected_state
+        .users
+        .values()
+        .filter_map(|user| user.steam_id.account_id())
+    {
+        let text = account_id.to_string();
+        for data in user_info.iter() {
+            assert!(!data
+                .windows(4)
+                .any(|window| window == account_id.to_le_bytes()));
+            assert!(!data
+                .windows(text.len())
+                .any(|window| window == text.as_bytes()));
+        }
+    }
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_compact_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let reencoded = transform(&file, Vec::new());
+    let mut transforms = vec![transform_by_name("compact").unwrap()];
+    let compacted = transform_demo(&file, &mut transforms).unwrap().data;
+    assert!(compacted.len() < reencoded.len());
+    // temp entities are only removed by compact_all
+    assert!(!transforms[0].report().unwrap().contains("TempEntities"));
+
+    let (expected_header, expected_state) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+    let (header, state) = DemoParser::new(Demo::new(&compacted).get_stream())
+        .parse()
+        .unwrap();
+    assert_eq!(expected_header.ticks, header.ticks);
+    pretty_assertions::assert_eq!(expected_state, state);
+}
+
+#[test]
+fn transform_compact_all_test() {
+    let state = ParserState::new(24, |_| true, true);
+    let temp_entities = || Message::TempEntities(TempEntitiesMessage { events: Vec::new() });
+
+    // temp entities can contain gameplay data and are only removed by compact_all
+    let mut compact = transform_by_name("compact").unwrap();
+    assert!(compact.transform_message(&mut temp_entities(), &state));
+    let mut compact_all = transform_by_name("compact_all").unwrap();
+    assert!(!compact_all.transform_message(&mut temp_entities(), &state));
+    assert!(compact_all.report().unwrap().contains("TempEntities"));
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_recompress_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let mut transforms: Vec<Box<dyn Transform>> = vec![Box::new(Recompress::default())];
+    let recompressed = transform_demo(&file, &mut transforms).unwrap().data;
+    assert_ne!(Some("0 compressed tables".into()), transforms[0].report());
+
+    // the compressed tables are about the size of the originals
+    let difference = (recompressed.len() as f64 - file.len() as f64).abs();
+    assert!(
+        difference < file.len() as f64 * 0.01,
+        "re-encoded demo is {} bytes, original is {} bytes",
+        recompressed.len(),
+        file.len()
+    );
+
+    let (_, expected_state) = DemoParser::new(Demo::new(&file).get_stream())
+        .parse()
+        .unwrap();
+    let (_, state) = DemoParser::new(Demo::new(&recompressed).get_stream())
+        .parse()
+        .unwrap();
+    pretty_assertions::assert_eq!(expected_state, state);
+}
+
+#[test_case("small.dem"; "small.dem")]
+fn transform_truncated_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+
+    let transformed = transform_demo(&file[0..file.len() - 1000], &mut []).unwrap();
+    assert!(transformed.incomplete);
+
+    // the partial packet is dropped and the output is a complete demo
+    let (header, _) = DemoParser::new(Demo::new(&transformed.data).get_stream())
+        .parse()
+        .unwrap();
+    assert!(header.ticks > 0);
+    assert!(header.frames > 0);
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use test_case::test_case;
+
+use bitbuffer::BitRead;
+use tf_demo_parser::demo::header::Header;
+use tf_demo_parser::demo::packet::Packet;
+use tf_demo_parser::demo::parser::RawPacketStream;
+use tf_demo_parser::{Demo, DemoParser, DemoWriter};
+
+#[test_case("small.dem"; "small.dem")]
+fn writer_test(input_file: &str) {
+    let file = fs::read(format!("test_data/{}", input_file)).expect("Unable to read file");
+    let demo = Demo::new(&file);
+    let mut stream = demo.get_stream();
+    let mut header = Header::read(&mut stream).unwrap();
+    let expected_header = header.clone();
+
+    // the writer should fill in the length of the demo
+    header.ticks = 0;
+    header.frames = 0;
+    header.duration = 0.0;
+
+    let mut packets = RawPacketStream::new(stream);
+    let mut writer = DemoWriter::new(header);
+    while let Some(packet) = packets.next(writer.state()).unwrap() {
+        // leave out the stop packet, the writer should add one
+        if !matches!(packet, Packet::Stop(_)) {
+            writer.write_packet(packet).unwrap();
+        }
+    }
+    let out = writer.finish().unwrap();
+
+    let written = Demo::new(&out);
+    let mut written_stream = written.get_stream();
+    let written_header = Header::read(&mut written_stream).unwrap();
+    assert_eq!(expected_header.ticks, written_header.ticks);
+    assert_eq!(expected_header.frames, written_header.frames);
+    assert!((expected_header.duration - written_header.duration).abs() < 0.001);
+
+    let (_, expected_state) = DemoParser::new(demo.get_stream()).parse().unwrap();
+    let (_, state) = DemoParser::new(written.get_stream()).parse().unwrap();
+    pretty_assertions::assert_eq!(expected_state, state);
+}